@@ -0,0 +1,51 @@
+// cargo bench --bench rect_batch
+// the cpu side of the word rectangles of a frame, one mesh per rectangle against one RectBatch
+// the upload and the draw calls need a window, they aren't part of it
+use ggez::graphics::{ self, Color, Rect };
+
+use std::hint::black_box;
+use std::time::{ Duration, Instant };
+
+use type_racer::draw_helper::RectBatch;
+
+const FRAMES: u32 = 2000;
+
+fn word_rects(words: usize) -> Vec<Rect> {
+    (0 .. words).map(|index| Rect::new((index * 7 % 1100) as f32, (index * 31 % 900) as f32, 120.0, 3.0)).collect()
+}
+
+fn per_rect(rects: &[Rect]) -> Duration {
+    let start = Instant::now();
+    for _ in 0 .. FRAMES {
+        for rect in rects {
+            let mut builder = graphics::MeshBuilder::new();
+            builder.rectangle(graphics::DrawMode::fill(), *rect, Color::WHITE).unwrap();
+            black_box(builder);
+        }
+    }
+
+    start.elapsed()
+}
+
+fn batched(rects: &[Rect]) -> Duration {
+    let start = Instant::now();
+    for _ in 0 .. FRAMES {
+        let mut batch = RectBatch::new();
+        for rect in rects {
+            batch.add(*rect, Color::WHITE);
+        }
+        black_box(batch.take_builder().unwrap());
+    }
+
+    start.elapsed()
+}
+
+fn main() {
+    for words in [50, 100, 200] {
+        let rects = word_rects(words);
+        let per_rect = per_rect(&rects).as_secs_f64() * 1_000_000.0 / FRAMES as f64;
+        let batched = batched(&rects).as_secs_f64() * 1_000_000.0 / FRAMES as f64;
+
+        println!("{} words: {:.1}us per frame with a mesh each ({} draw calls), {:.1}us batched (1 draw call)", words, per_rect, words, batched);
+    }
+}
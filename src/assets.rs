@@ -1,44 +1,182 @@
 use ggez::audio::{self, SoundSource};
-use ggez::{graphics, GameResult, Context};
+use ggez::{filesystem, graphics, GameResult, Context};
 use ggez::graphics::Color;
 use ggez::mint::Point2;
 use std::fmt::Debug;
+use std::path::PathBuf;
+
+// a sound that stays silent when it couldn't be loaded, or when there is no audio device at all
+#[derive(Default)]
+pub struct Sound {
+    source: Option<audio::Source>
+}
+
+impl Sound {
+    pub fn silent() -> Sound {
+        Sound::default()
+    }
+
+    pub fn load(ctx: &mut Context, path: &str, audio_enabled: bool) -> Sound {
+        if !audio_enabled {
+            return Sound::silent();
+        }
+
+        Sound::try_load(ctx, path).unwrap_or_else(|error| {
+            eprintln!("Couldn't load the sound {}, keeping it silent: {}", path, error);
+            Sound::silent()
+        })
+    }
+
+    fn try_load(ctx: &mut Context, path: &str) -> GameResult<Sound> {
+        Ok(Sound { source: Some(audio::Source::new(ctx, path)?) })
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.source.is_none()
+    }
+
+    pub fn play(&mut self, ctx: &mut Context) -> GameResult<()> {
+        match &mut self.source {
+            Some(source) => source.play(ctx),
+            None => Ok(())
+        }
+    }
+
+    pub fn stop(&mut self, ctx: &mut Context) -> GameResult<()> {
+        match &mut self.source {
+            Some(source) => source.stop(ctx),
+            None => Ok(())
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        if let Some(source) = &mut self.source {
+            source.set_volume(volume);
+        }
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        if let Some(source) = &mut self.source {
+            source.set_pitch(pitch);
+        }
+    }
+
+    pub fn set_repeat(&mut self, repeat: bool) {
+        if let Some(source) = &mut self.source {
+            source.set_repeat(repeat);
+        }
+    }
+}
 
 pub struct Assets {
-    pub word_typed_sound: audio::Source,
-    pub background_music: audio::Source
+    pub word_typed_sound: Sound,
+    pub menu_tick_sound: Sound,
+    pub menu_confirm_sound: Sound,
+    pub milestone_sound: Sound,
+    pub background_music: Sound,
+    // false when the game runs without an audio device, every sound is silent then
+    pub audio_enabled: bool,
+    // every "*-music.wav" file in the resources, sorted by name
+    pub music_tracks: Vec<String>,
+    // optional, the hud falls back to the text label when it's missing
+    pub heart_image: Option<graphics::Image>,
+    pub font: graphics::Font
 }
 
 impl Assets {
-    pub fn new(ctx: &mut Context) -> GameResult<Assets> {
-        let word_typed_sound = audio::Source::new(ctx, "/collect-point.wav")?;
-        let mut background_music = audio::Source::new(ctx, "/game-background-music.wav")?;
-        background_music.set_repeat(true);
+    pub const DEFAULT_MUSIC_TRACK: &'static str = "/game-background-music.wav";
+
+    pub fn new(ctx: &mut Context, music_track: &str, audio_enabled: bool) -> GameResult<Assets> {
+        let word_typed_sound = Sound::load(ctx, "/collect-point.wav", audio_enabled);
+        let menu_tick_sound = Sound::load(ctx, "/menu-tick.wav", audio_enabled);
+        let menu_confirm_sound = Sound::load(ctx, "/menu-confirm.wav", audio_enabled);
+        let milestone_sound = Sound::load(ctx, "/milestone.wav", audio_enabled);
+        let background_music = Assets::load_music(ctx, music_track, audio_enabled);
+        let music_tracks = Assets::music_tracks_from(filesystem::read_dir(ctx, "/")?.collect());
+        let heart_image = graphics::Image::new(ctx, "/heart.png").ok();
+        let font = Assets::font_or_default(graphics::Font::new(ctx, "/RedHatDisplay-Regular.otf"));
 
         Ok(Assets{
             word_typed_sound,
-            background_music
+            menu_tick_sound,
+            menu_confirm_sound,
+            milestone_sound,
+            background_music,
+            audio_enabled,
+            music_tracks,
+            heart_image,
+            font
         })
     }
+
+    // a missing or broken track falls back to the default one, and that one to silence
+    pub fn load_music(ctx: &mut Context, track: &str, audio_enabled: bool) -> Sound {
+        if !audio_enabled {
+            return Sound::silent();
+        }
+
+        let mut music = match Sound::try_load(ctx, track) {
+            Ok(music) => music,
+            Err(error) => {
+                eprintln!("Couldn't load the music track {}, falling back to the default one: {}", track, error);
+                Sound::load(ctx, Assets::DEFAULT_MUSIC_TRACK, audio_enabled)
+            }
+        };
+
+        music.set_repeat(true);
+        music
+    }
+
+    pub fn music_tracks_from(paths: Vec<PathBuf>) -> Vec<String> {
+        let mut tracks = paths.iter()
+            .filter_map(|path| path.to_str())
+            .filter(|path| path.ends_with("-music.wav"))
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        // the dev and the shipped resources can both be mounted
+        tracks.sort();
+        tracks.dedup();
+        tracks
+    }
+
+    // keeps the game playable with ggez's built-in font when the bundled one can't be loaded
+    pub fn font_or_default(loaded: GameResult<graphics::Font>) -> graphics::Font {
+        match loaded {
+            Ok(font) => font,
+            Err(error) => {
+                eprintln!("Couldn't load the font, falling back to the default one: {}", error);
+                graphics::Font::default()
+            }
+        }
+    }
 }
 
+// what a word needs from its text, the tests stand in a sprite with fixed dimensions
 pub trait Sprite: Debug {
     fn draw(&mut self, top_left: Point2<f32>, color: Color, ctx: &mut Context) -> GameResult<()>;
     fn width(&self, ctx: &mut Context) -> f32;
     fn height(&self, ctx: &mut Context) -> f32;
+    fn set_text(&mut self, text: &str);
+
+    // adds the sprite to the batch that `graphics::draw_queued_text` draws, sprites without a batch draw right away
+    fn queue(&mut self, top_left: Point2<f32>, color: Color, ctx: &mut Context) -> GameResult<()> {
+        self.draw(top_left, color, ctx)
+    }
 }
 
 #[derive(Debug)]
 pub struct TextSprite {
     text: graphics::Text,
+    font: graphics::Font,
+    font_size: f32
 }
 
 impl TextSprite {
-    pub fn new(label: &str, ctx: &mut Context, font_size: f32) -> GameResult<TextSprite> {
-        let font = graphics::Font::new(ctx, "/RedHatDisplay-Regular.otf")?;
+    pub fn new(label: &str, font: graphics::Font, font_size: f32) -> TextSprite {
         let mut text = graphics::Text::new(label);
         text.set_font(font, graphics::PxScale::from(font_size));
-        Ok(TextSprite { text })
+        TextSprite { text, font, font_size }
     }
 }
 
@@ -54,4 +192,14 @@ impl Sprite for TextSprite {
     fn height(&self, ctx: &mut Context) -> f32 {
         self.text.height(ctx)
     }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = graphics::Text::new(text);
+        self.text.set_font(self.font, graphics::PxScale::from(self.font_size));
+    }
+
+    fn queue(&mut self, top_left: Point2<f32>, color: Color, ctx: &mut Context) -> GameResult<()> {
+        graphics::queue_text(ctx, &self.text, top_left, Some(color));
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,26 @@
+pub const DEFAULT_WINDOW_SIZE: (f32, f32) = (1200.0, 1000.0);
+// below this the hud rows start to overlap
+pub const MIN_WINDOW_SIZE: (f32, f32) = (800.0, 600.0);
+
+// reads "--width <pixels>" and "--height <pixels>", the missing ones keep their default
+pub fn parse_window_size(args: &[String]) -> Result<(f32, f32), String> {
+    let (mut width, mut height) = DEFAULT_WINDOW_SIZE;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let size = match arg.as_str() {
+            "--width" => &mut width,
+            "--height" => &mut height,
+            _ => return Err(format!("Unknown argument {}", arg))
+        };
+
+        let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+        *size = value.parse::<f32>().map_err(|_| format!("Invalid value {} for {}", value, arg))?;
+    }
+
+    if width < MIN_WINDOW_SIZE.0 || height < MIN_WINDOW_SIZE.1 {
+        return Err(format!("The window can't be smaller than {}x{}", MIN_WINDOW_SIZE.0, MIN_WINDOW_SIZE.1));
+    }
+
+    Ok((width, height))
+}
@@ -1,21 +1,74 @@
 use ggez::{ Context, GameResult };
 use ggez::graphics;
 
+use std::collections::VecDeque;
+
 // run with
 // DEBUG=1 cargo run
 pub fn is_active() -> bool {
     std::env::var("DEBUG").is_ok()
 }
 
-pub fn draw_outline(bounding_box: graphics::Rect, ctx: &mut Context) -> GameResult<()> {
+// all the outlines go in one mesh, so they cost a single draw call
+pub fn draw_outlines(bounding_boxes: &[graphics::Rect], ctx: &mut Context) -> GameResult<()> {
+    if bounding_boxes.is_empty() {
+        return Ok(());
+    }
+
     let draw_mode = graphics::DrawMode::Stroke(graphics::StrokeOptions::default().with_line_width(1.0));
     let red = graphics::Color::from_rgb(255, 0, 0);
-    let outline = graphics::MeshBuilder::new().
-        rectangle(draw_mode, bounding_box, red).
-        unwrap().
-        build(ctx).
-        unwrap();
-    
-    graphics::draw(ctx, &outline, graphics::DrawParam::default())?;
-    Ok(())
+    let mut builder = graphics::MeshBuilder::new();
+    for bounding_box in bounding_boxes {
+        builder.rectangle(draw_mode, *bounding_box, red)?;
+    }
+
+    let outlines = builder.build(ctx)?;
+    graphics::draw(ctx, &outlines, graphics::DrawParam::default())
+}
+
+// durations of the latest frames, to spot stutter from the per frame work
+#[derive(Debug)]
+pub struct FrameTimes {
+    window: usize,
+    durations: VecDeque<f32>,
+    since_report: usize
+}
+
+impl FrameTimes {
+    pub fn new(window: usize) -> FrameTimes {
+        FrameTimes {
+            window,
+            durations: VecDeque::with_capacity(window),
+            since_report: 0
+        }
+    }
+
+    // returns true once every `window` frames, when it's time to print the summary
+    pub fn record(&mut self, seconds: f32) -> bool {
+        if self.durations.len() == self.window {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(seconds);
+
+        self.since_report += 1;
+        if self.since_report < self.window {
+            return false;
+        }
+
+        self.since_report = 0;
+        true
+    }
+
+    // min, max and average of the window in seconds
+    pub fn summary(&self) -> Option<(f32, f32, f32)> {
+        if self.durations.is_empty() {
+            return None;
+        }
+
+        let min = self.durations.iter().cloned().fold(f32::MAX, f32::min);
+        let max = self.durations.iter().cloned().fold(0.0, f32::max);
+        let avg = self.durations.iter().sum::<f32>() / self.durations.len() as f32;
+
+        Some((min, max, avg))
+    }
 }
\ No newline at end of file
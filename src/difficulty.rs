@@ -0,0 +1,276 @@
+use rand::Rng;
+use serde::{ Deserialize, Serialize };
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::settings::Settings;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    // horizontal speed of the spawned words in pixels per second, min is always below max
+    pub fn word_speed_range(&self) -> (f32, f32) {
+        match self {
+            Difficulty::Easy => (70.0, 200.0),
+            Difficulty::Normal => (100.0, 300.0),
+            Difficulty::Hard => (150.0, 400.0)
+        }
+    }
+
+    pub fn random_word_speed<R: Rng>(&self, rng: &mut R) -> f32 {
+        let (min_speed, max_speed) = self.word_speed_range();
+        debug_assert!(min_speed < max_speed, "invalid word speed range for {}", self);
+
+        rng.gen_range(min_speed .. max_speed)
+    }
+
+    // percent of the spawned words that change their color
+    pub fn color_changing_chance(&self) -> u8 {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 30,
+            Difficulty::Hard => 40
+        }
+    }
+
+    // seconds at the start of a run when the escaped words cost nothing
+    pub fn warmup_duration(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 15.0,
+            Difficulty::Normal => 10.0,
+            Difficulty::Hard => 0.0
+        }
+    }
+
+    // words at the start of a run that spawn slowed down, with the grace turned on
+    pub fn grace_words(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2
+        }
+    }
+
+    // share of the slowest regular speed the first grace word is capped at
+    pub fn grace_speed_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 0.6,
+            Difficulty::Hard => 0.75
+        }
+    }
+
+    // the cap climbs back to the fastest speed over the grace words, none once they all spawned
+    pub fn grace_speed_cap(&self, spawned_words: u32) -> Option<f32> {
+        let grace_words = self.grace_words();
+        if spawned_words >= grace_words {
+            return None;
+        }
+
+        let (min_speed, max_speed) = self.word_speed_range();
+        let start = min_speed * self.grace_speed_factor();
+        Some(start + (max_speed - start) * spawned_words as f32 / grace_words as f32)
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy
+        }
+    }
+}
+
+// what the auto difficulty picked for the player, from the scores of the earlier runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoTuning {
+    pub difficulty: Difficulty,
+    pub starting_lifes: u32,
+    // multiplier of the spawn rate, above 1 the words come more often
+    pub spawn_rate: f32
+}
+
+// typical scores of the players that fit an easier or a harder run
+pub const AUTO_EASY_BELOW: f32 = 300.0;
+pub const AUTO_HARD_FROM: f32 = 1500.0;
+pub const AUTO_EXPERT_FROM: f32 = 4000.0;
+
+// tuned from the median of the saved scores, so one lucky or unlucky run doesn't decide it
+// a player without any saved score gets the normal difficulty
+pub fn auto_tuning(scores: &[f32], starting_lifes: u32) -> AutoTuning {
+    let normal = AutoTuning { difficulty: Difficulty::Normal, starting_lifes, spawn_rate: 1.0 };
+
+    let mut scores = scores.iter().copied().filter(|score| score.is_finite()).collect::<Vec<f32>>();
+    if scores.is_empty() {
+        return normal;
+    }
+
+    scores.sort_by(|a, b| a.total_cmp(b));
+    let middle = scores.len() / 2;
+    let median = if scores.len() % 2 == 0 { (scores[middle - 1] + scores[middle]) / 2.0 } else { scores[middle] };
+
+    if median < AUTO_EASY_BELOW {
+        AutoTuning { difficulty: Difficulty::Easy, starting_lifes: starting_lifes + 1, spawn_rate: 0.85 }
+    }
+    else if median < AUTO_HARD_FROM {
+        normal
+    }
+    else if median < AUTO_EXPERT_FROM {
+        AutoTuning { difficulty: Difficulty::Hard, ..normal }
+    }
+    else {
+        AutoTuning { difficulty: Difficulty::Hard, starting_lifes: starting_lifes.saturating_sub(1).max(1), spawn_rate: 1.2 }
+    }
+}
+
+// true in `percent` out of 100 rolls, 0 never fires and 100 always does
+pub fn roll_chance<R: Rng>(rng: &mut R, percent: u8) -> bool {
+    debug_assert!(percent <= 100, "chance of {}% is out of range", percent);
+
+    rng.gen_range(0 .. 100) < percent
+}
+
+pub const MAX_REPEAT_REROLLS: usize = 5;
+
+// random word of the pool other than `exclude`, unless the pool has no other word
+pub fn pick_word<R: Rng>(rng: &mut R, pool: &[String], exclude: Option<&str>) -> Option<String> {
+    if pool.is_empty() {
+        return None;
+    }
+
+    let mut index = rng.gen_range(0 .. pool.len());
+    for _ in 0 .. MAX_REPEAT_REROLLS {
+        if exclude != Some(pool[index].as_str()) {
+            return Some(pool[index].clone());
+        }
+
+        index = rng.gen_range(0 .. pool.len());
+    }
+
+    // out of rerolls, the next different word after the last roll
+    (0 .. pool.len())
+        .map(|offset| &pool[(index + offset) % pool.len()])
+        .find(|word| exclude != Some(word.as_str()))
+        .or(Some(&pool[index]))
+        .cloned()
+}
+
+pub const SPAWN_SPEED_UP_STEP: f32 = 0.03;
+
+// seconds until the next spawn, both bounds shrink as the run speeds up
+pub fn spawn_gap_range(speed_up: f32) -> (f32, f32) {
+    (3.0 - speed_up, 3.5 - speed_up)
+}
+
+// the double speed modifier, the rewards follow the word speed so they scale with it
+pub const CHALLENGE_SPEED_FACTOR: f32 = 1.5;
+
+pub fn speed_factor(double_speed: bool) -> f32 {
+    if double_speed { CHALLENGE_SPEED_FACTOR } else { 1.0 }
+}
+
+// the gap shrinks as the spawn rate grows by `factor`, min stays below max
+pub fn scaled_spawn_gap_range(speed_up: f32, factor: f32) -> (f32, f32) {
+    let (min_gap, max_gap) = spawn_gap_range(speed_up);
+
+    (min_gap / factor, max_gap / factor)
+}
+
+// every spawn speeds the next ones up, unless the ramp is turned off
+pub fn next_speed_up(speed_up: f32, ramp: bool) -> f32 {
+    if ramp {
+        speed_up + SPAWN_SPEED_UP_STEP
+    }
+    else {
+        speed_up
+    }
+}
+
+// typists at this speed keep the regular spawn gap
+pub const ADAPTIVE_BASELINE_WPM: f32 = 40.0;
+pub const MIN_SPAWN_GAP_SCALE: f32 = 0.6;
+pub const MAX_SPAWN_GAP_SCALE: f32 = 1.5;
+
+// multiplier of the time between two spawns, faster typists get shorter gaps
+pub fn spawn_gap_scale(wpm: f32) -> f32 {
+    if wpm <= 0.0 {
+        return MAX_SPAWN_GAP_SCALE;
+    }
+
+    (ADAPTIVE_BASELINE_WPM / wpm).clamp(MIN_SPAWN_GAP_SCALE, MAX_SPAWN_GAP_SCALE)
+}
+
+// with the last stand on, a player down to the last life only faces one word at a time
+pub fn holds_spawn(last_stand: bool, remaining_lifes: u32, words_on_screen: usize) -> bool {
+    last_stand && remaining_lifes == 1 && words_on_screen > 0
+}
+
+pub const FIRST_WAVE_SIZE: usize = 5;
+pub const WAVE_SIZE_STEP: usize = 2;
+
+// words spawned by the `wave`th wave, counted from 1
+pub fn wave_size(wave: u32) -> usize {
+    FIRST_WAVE_SIZE + WAVE_SIZE_STEP * (wave.max(1) - 1) as usize
+}
+
+// takes one life, true when it was the last one
+pub fn lose_life(remaining_lifes: &mut u32) -> bool {
+    *remaining_lifes = remaining_lifes.saturating_sub(1);
+    *remaining_lifes == 0
+}
+
+// what an (Enter) with input that matches no word costs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailedSubmit {
+    pub penalty: f32,
+    pub loses_life: bool,
+    pub ends_run: bool
+}
+
+// none when neither the miss penalty nor the strict submit is turned on, the submit is ignored then
+// an input that finishes a word on screen is no miss, even before the word got taken
+pub fn failed_submit(settings: &Settings, remaining_lifes: u32, finishes_word: bool) -> Option<FailedSubmit> {
+    if finishes_word || (settings.miss_penalty <= 0.0 && !settings.strict_submit) {
+        return None;
+    }
+
+    let mut remaining_lifes = remaining_lifes;
+    let ends_run = settings.strict_submit && lose_life(&mut remaining_lifes);
+
+    Some(FailedSubmit {
+        penalty: settings.miss_penalty.max(0.0),
+        loses_life: settings.strict_submit,
+        ends_run
+    })
+}
+
+// the player's own limit on the words at once, on any difficulty, 0 turns it off
+pub fn board_full(words_on_screen: usize, max_visible_words: usize) -> bool {
+    max_visible_words > 0 && words_on_screen >= max_visible_words
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL.iter()
+            .find(|difficulty| difficulty.to_string() == value)
+            .copied()
+            .ok_or(format!("Unknown difficulty {}", value))
+    }
+}
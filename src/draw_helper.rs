@@ -1,7 +1,8 @@
-use ggez:: {graphics, Context };
-use ggez::mint::Point2;
+use ggez:: {graphics, Context, GameResult };
+use ggez::mint::{ Point2, Vector2 };
+use rand::Rng;
 
-pub fn format_scoreboard(scoreboard: &Vec<String>) -> String {
+pub fn format_scoreboard(scoreboard: &[String]) -> String {
     let mut result = String::new();
 
     for (index,score) in scoreboard.iter().enumerate()
@@ -30,7 +31,177 @@ pub fn draw_text_background(text_pos: Point2<f32>, text_width: f32, text_height:
     graphics::draw(ctx, &background_mesh, graphics::DrawParam::default()).unwrap();
 }
 
+// frame of fading bands, strongest at the edges of the screen
+pub fn draw_vignette(width: f32, height: f32, color: graphics::Color, ctx: &mut Context) {
+    const BANDS: u32 = 12;
+    const BAND_WIDTH: f32 = 6.0;
+
+    let mut builder = graphics::MeshBuilder::new();
+    for band in 0 .. BANDS {
+        let inset = band as f32 * BAND_WIDTH + BAND_WIDTH / 2.0;
+        let alpha = 0.5 * (1.0 - band as f32 / BANDS as f32);
+        let band_color = graphics::Color::new(color.r, color.g, color.b, alpha);
+        let draw_mode = graphics::DrawMode::Stroke(graphics::StrokeOptions::default().with_line_width(BAND_WIDTH));
+        let rect = graphics::Rect::new(inset, inset, width - 2.0 * inset, height - 2.0 * inset);
+
+        builder.rectangle(draw_mode, rect, band_color).unwrap();
+    }
+
+    let vignette = builder.build(ctx).unwrap();
+    graphics::draw(ctx, &vignette, graphics::DrawParam::default()).unwrap();
+}
+
+// filled rects gathered over a frame and drawn as a single mesh, one draw call however many words add one
+#[derive(Debug, Default)]
+pub struct RectBatch {
+    rects: Vec<(graphics::Rect, graphics::Color)>
+}
+
+impl RectBatch {
+    pub fn new() -> Self {
+        RectBatch::default()
+    }
+
+    pub fn add(&mut self, rect: graphics::Rect, color: graphics::Color) {
+        self.rects.push((rect, color));
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    // empties the batch into the vertices of one mesh, the rects added later end up on top
+    pub fn take_builder(&mut self) -> GameResult<graphics::MeshBuilder> {
+        let mut builder = graphics::MeshBuilder::new();
+        for (rect, color) in self.rects.drain(..) {
+            builder.rectangle(graphics::DrawMode::fill(), rect, color)?;
+        }
+
+        Ok(builder)
+    }
+
+    // empties the batch, an empty one draws nothing since a mesh needs vertices
+    pub fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        if self.rects.is_empty() {
+            return Ok(());
+        }
+
+        let mesh = self.take_builder()?.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+    }
+}
+
+// range of the spawned words' top edge, keeping them clear of the hud rows and fully on screen
+pub fn spawn_band(screen_height: f32, top_hud_height: f32, bottom_hud_height: f32, word_height: f32, margin: f32) -> (f32, f32) {
+    let min_y = top_hud_height + margin;
+    let max_y = screen_height - bottom_hud_height - word_height - margin;
+
+    // a window too small for the hud still gets a valid range
+    (min_y, max_y.max(min_y + 1.0))
+}
+
+// eases from 0 up to `max` and back once every `period` seconds
+pub fn breathing_level(time: f32, period: f32, max: f32) -> f32 {
+    max * (1.0 - (2.0 * std::f32::consts::PI * time / period).cos()) / 2.0
+}
+
+// top left corner on the left edge, inside the band and clear of the `occupied` rects
+// none once `max_tries` random candidates all overlapped something
+pub fn find_spawn_point<R: Rng>(rng: &mut R, band: (f32, f32), size: Vector2<f32>, occupied: &[graphics::Rect], max_tries: usize) -> Option<Point2<f32>> {
+    let (min_y, max_y) = band;
+
+    (0 .. max_tries)
+        .map(|_| Point2 { x: 0.0, y: rng.gen_range(min_y .. max_y) })
+        .find(|point| {
+            let candidate = graphics::Rect::new(point.x, point.y, size.x, size.y);
+            !occupied.iter().any(|rect| rect.overlaps(&candidate))
+        })
+}
+
+// top edges of up to `lanes` evenly spaced rows across the band, at least a word height apart
+// so the words of two lanes never overlap, fewer lanes fit when the band is too short for all of them
+pub fn lane_tops(band: (f32, f32), word_height: f32, lanes: usize) -> Vec<f32> {
+    let (min_y, max_y) = band;
+    let range = (max_y - min_y).max(0.0);
+    let fitting = if word_height > 0.0 { (range / word_height).floor() as usize + 1 } else { lanes };
+    let lanes = lanes.min(fitting).max(1);
+
+    if lanes == 1 {
+        return vec![min_y + range / 2.0];
+    }
+
+    let spacing = range / (lanes - 1) as f32;
+    (0 .. lanes).map(|lane| min_y + lane as f32 * spacing).collect()
+}
+
+// top edge of a random lane whose spawn spot at the left edge is clear of the `occupied` rects
+// none when every lane is taken
+pub fn pick_lane<R: Rng>(rng: &mut R, lane_tops: &[f32], size: Vector2<f32>, occupied: &[graphics::Rect]) -> Option<f32> {
+    // touching edges don't count, the lanes are exactly a word height apart
+    let is_free = |top: f32| !occupied.iter().any(|rect| rect.left() < size.x && rect.top() < top + size.y && rect.bottom() > top);
+    let free = lane_tops.iter().copied().filter(|top| is_free(*top)).collect::<Vec<f32>>();
+    if free.is_empty() {
+        return None;
+    }
+
+    Some(free[rng.gen_range(0 .. free.len())])
+}
+
+// badge next to the input while the typed letters come out upper case
+pub fn modifier_badge(shift: bool, caps_lock: bool) -> Option<&'static str> {
+    match (shift, caps_lock) {
+        (true, true) => Some("SHIFT CAPS"),
+        (true, false) => Some("SHIFT"),
+        (false, true) => Some("CAPS"),
+        (false, false) => None
+    }
+}
+
+// green for a word that just spawned, turning red as it closes in on the right edge
+pub fn urgency_color(progress: f32) -> graphics::Color {
+    let progress = progress.clamp(0.0, 1.0);
+    graphics::Color::new(progress, 1.0 - progress, 0.0, 1.0)
+}
+
+// shrink factor for the font of a text wider than `max_width`, text that fits keeps its size
+pub fn fit_scale(width: f32, max_width: f32) -> f32 {
+    if width <= max_width || width <= 0.0 {
+        1.0
+    }
+    else {
+        max_width / width
+    }
+}
+
 pub fn translate(pos: &mut Point2<f32>, trans: &Point2<f32>) {
     pos.x += trans.x;
     pos.y += trans.y;
-}
\ No newline at end of file
+}
+
+// hebrew and arabic blocks, including their presentation forms
+pub fn is_rtl(text: &str) -> bool {
+    text.chars().any(|letter| matches!(letter,
+        '\u{0590}' ..= '\u{08FF}' |
+        '\u{FB1D}' ..= '\u{FDFF}' |
+        '\u{FE70}' ..= '\u{FEFF}'))
+}
+
+// the text renderer always lays glyphs out left to right, so rtl text is reversed for display only
+pub fn visual_order(text: &str) -> String {
+    if is_rtl(text) {
+        return text.chars().rev().collect();
+    }
+
+    String::from(text)
+}
+
+// seconds as "m:ss"
+pub fn format_elapsed(seconds: f32) -> String {
+    let total_seconds = seconds.max(0.0) as u32;
+
+    format!("{}:{:02}", total_seconds / 60, total_seconds % 60)
+}
@@ -0,0 +1,186 @@
+// buff prices and word rewards, kept together so the balance can be tuned in one place
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Economy {
+    pub buy_life_tax: f32,
+    pub remove_words_tax: f32,
+    pub slow_word_spawn_tax: f32,
+    pub shield_tax: f32,
+    pub magnet_tax: f32,
+    pub color_changing_multiplier: f32,
+    // share of a word's reward that is also paid out as cash
+    pub cash_rate: f32,
+    // seconds between two typed words that keep a combo going
+    pub combo_window: f32,
+    // extra cash for every word already in the combo
+    pub combo_bonus_step: f32,
+    // paid for every wave number when a whole wave is cleared without an escape
+    pub wave_bonus_step: f32,
+    // cash the panic button takes for every second the escapes are forgiven
+    pub panic_cash_per_second: f32
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Economy {
+            buy_life_tax: 300.0,
+            remove_words_tax: 350.0,
+            slow_word_spawn_tax: 1000.0,
+            shield_tax: 500.0,
+            magnet_tax: 400.0,
+            color_changing_multiplier: 2.0,
+            cash_rate: 1.0,
+            combo_window: 1.5,
+            combo_bonus_step: 5.0,
+            wave_bonus_step: 50.0,
+            panic_cash_per_second: 100.0
+        }
+    }
+}
+
+impl Economy {
+    // faster and longer words are worth more
+    pub fn reward(&self, speed: f32, label: &str, is_color_changing: bool) -> f32 {
+        let color_multi = if is_color_changing { self.color_changing_multiplier } else { 1.0 };
+
+        speed * color_multi * (label.len() as f32) / 100.0
+    }
+
+    pub fn cash_for(&self, reward: f32) -> f32 {
+        reward * self.cash_rate
+    }
+
+    // `combo` counts the words chained before this one, a slow word pays nothing
+    pub fn combo_bonus(&self, interval: f32, combo: u32) -> f32 {
+        if interval > self.combo_window {
+            return 0.0;
+        }
+
+        self.combo_bonus_step * combo as f32
+    }
+
+    // later waves are longer, so clearing them pays more
+    pub fn wave_bonus(&self, wave: u32) -> f32 {
+        self.wave_bonus_step * wave as f32
+    }
+
+    // the more cash the panic button took, the longer the escapes are forgiven
+    pub fn panic_invulnerability(&self, spent: f32) -> f32 {
+        if self.panic_cash_per_second <= 0.0 {
+            return 0.0;
+        }
+
+        (spent / self.panic_cash_per_second).clamp(0.0, MAX_PANIC_INVULNERABILITY)
+    }
+
+    // every preset makes one more buff affordable from the start
+    pub fn starting_cash_presets(&self) -> [f32; 4] {
+        [0.0, self.buy_life_tax, self.remove_words_tax, self.slow_word_spawn_tax]
+    }
+
+    // share of the cheapest buff the player can't afford yet, none once every buff is affordable
+    pub fn next_buff_progress(&self, cash: f32) -> Option<f32> {
+        [self.buy_life_tax, self.remove_words_tax, self.slow_word_spawn_tax, self.shield_tax, self.magnet_tax].iter()
+            .filter(|tax| cash < **tax)
+            .min_by(|a, b| a.total_cmp(b))
+            .map(|tax| (cash / tax).max(0.0))
+    }
+}
+
+
+// seconds of forgiven escapes the panic button tops out at, however much it took
+pub const MAX_PANIC_INVULNERABILITY: f32 = 8.0;
+
+// the latest change of the cash, shown next to it for a moment, quick changes add up
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CashDelta {
+    amount: f32,
+    time_left: f32
+}
+
+impl CashDelta {
+    pub const DURATION: f32 = 1.5;
+
+    pub fn record(&mut self, amount: f32) {
+        if amount == 0.0 {
+            return;
+        }
+
+        if self.time_left <= 0.0 {
+            self.amount = 0.0;
+        }
+
+        self.amount += amount;
+        self.time_left = CashDelta::DURATION;
+    }
+
+    pub fn update(&mut self, seconds: f32) {
+        self.time_left = (self.time_left - seconds).max(0.0);
+    }
+
+    pub fn amount(&self) -> f32 {
+        self.amount
+    }
+
+    // none once it faded out, or when the changes cancelled each other out
+    pub fn label(&self) -> Option<String> {
+        if self.time_left <= 0.0 || self.amount == 0.0 {
+            return None;
+        }
+
+        Some(format!("{:+.2}", self.amount))
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.time_left / CashDelta::DURATION
+    }
+}
+
+// the cash of a run, every change goes through it so none is missed by the hud and it never goes below zero
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Wallet {
+    cash: f32,
+    delta: CashDelta
+}
+
+impl Wallet {
+    pub fn new(cash: f32) -> Self {
+        Wallet { cash: cash.max(0.0), delta: CashDelta::default() }
+    }
+
+    pub fn cash(&self) -> f32 {
+        self.cash
+    }
+
+    pub fn delta(&self) -> &CashDelta {
+        &self.delta
+    }
+
+    pub fn add(&mut self, amount: f32) {
+        let amount = amount.max(0.0);
+        self.cash += amount;
+        self.delta.record(amount);
+    }
+
+    // a price the wallet can't afford leaves it untouched
+    pub fn spend(&mut self, amount: f32) -> bool {
+        if self.cash < amount {
+            return false;
+        }
+
+        self.cash -= amount;
+        self.delta.record(-amount);
+        true
+    }
+
+    // takes as much of the penalty as the cash covers without going below zero, returns what was taken
+    pub fn take(&mut self, penalty: f32) -> f32 {
+        let taken = penalty.clamp(0.0, self.cash.max(0.0));
+        self.cash -= taken;
+        self.delta.record(-taken);
+        taken
+    }
+
+    pub fn update(&mut self, seconds: f32) {
+        self.delta.update(seconds);
+    }
+}
\ No newline at end of file
@@ -1,35 +1,81 @@
 use ggez::{ Context, GameResult, graphics };
+use ggez::graphics::Color;
 use ggez::mint::{ Point2, Vector2 };
 
 use rand::Rng;
 use rand::rngs::ThreadRng;
 
 use crate::assets::Sprite;
+use crate::draw_helper::{ self, RectBatch };
+use crate::economy::Economy;
 
 #[derive(Debug)]
 pub struct Word {
     pub pos: Point2<f32>,
     pub is_typed: bool,
     pub is_color_changing: bool,
+    // the color animation is turned off, a color changing word gets a steady color and an underline instead
+    pub is_color_frozen: bool,
+    // the current input is a prefix of the label
+    pub is_targeted: bool,
+    // drawn see-through, typing it still works
+    pub is_dimmed: bool,
+    // drops its last character every `SHRINK_INTERVAL` seconds until it's typed
+    pub is_shrinking: bool,
+    shrink_timer: f32,
+    escaped: bool,
+    default_color: Color,
+    typed_color: Color,
     real_pos: Point2<f32>,
     rng: ThreadRng,
     label: String,
     velocity: Vector2<f32>,
+    // measured once at spawn, so the rect math doesn't need a context
+    size: Option<Vector2<f32>>,
     sprite: Box<dyn Sprite>
 }
 
 impl Word {
+    pub const DEFAULT_COLOR: Color = Color::WHITE;
+    pub const TYPED_COLOR: Color = Color::new(1.0, 0.84, 0.0, 1.0);
+    // words escape when their right edge gets this close to the right side of the screen
+    pub const ESCAPE_MARGIN: f32 = 10.0;
+    pub const DIMMED_ALPHA: f32 = 0.3;
+    // frozen color changing words, the underline tells them apart without relying on the hue
+    pub const FROZEN_BONUS_COLOR: Color = Color::new(0.35, 0.8, 1.0, 1.0);
+    pub const BONUS_UNDERLINE_HEIGHT: f32 = 3.0;
+    pub const SHRINK_INTERVAL: f32 = 3.0;
+    // shrinking words stop at this many characters
+    pub const MIN_SHRINK_LEN: usize = 2;
+
     pub fn new(label: &str, pos: Point2<f32>, speed: f32, sprite: Box<dyn Sprite>, is_color_changing: bool) -> GameResult<Self> {
+        Word::build(label, pos, speed, sprite, None, is_color_changing)
+    }
+
+    pub fn with_size(label: &str, pos: Point2<f32>, speed: f32, sprite: Box<dyn Sprite>, size: Vector2<f32>, is_color_changing: bool) -> GameResult<Self> {
+        Word::build(label, pos, speed, sprite, Some(size), is_color_changing)
+    }
+
+    fn build(label: &str, pos: Point2<f32>, speed: f32, sprite: Box<dyn Sprite>, size: Option<Vector2<f32>>, is_color_changing: bool) -> GameResult<Self> {
         let label = String::from(label);
 
         Ok(Word {
             pos,
             is_typed: false,
             is_color_changing,
+            is_color_frozen: false,
+            is_targeted: false,
+            is_dimmed: false,
+            is_shrinking: false,
+            shrink_timer: 0.0,
+            escaped: false,
+            default_color: Word::DEFAULT_COLOR,
+            typed_color: Word::TYPED_COLOR,
             real_pos: pos,
             rng: rand::thread_rng(),
             label,
             velocity: Vector2 { x: speed, y: 0.0 },
+            size,
             sprite
         })
     }
@@ -38,12 +84,95 @@ impl Word {
         self.label.as_str()
     }
 
-    pub fn update(&mut self, seconds: f32) {
+    pub fn speed(&self) -> f32 {
+        self.velocity.x
+    }
+
+    // apostrophes and hyphens show up inside words like "don't" and "well-being"
+    pub fn is_typeable(character: char) -> bool {
+        character.is_alphabetic() || character == '\'' || character == '-'
+    }
+
+    // how far the word got across the screen, 0 at the left edge and 1 at the right one
+    pub fn progress_ratio(&self, screen_width: f32) -> f32 {
+        if screen_width <= 0.0 {
+            return 0.0;
+        }
+
+        (self.pos.x / screen_width).clamp(0.0, 1.0)
+    }
+
+    // compares chars in typing order, which is also the storage order of rtl labels
+    pub fn matches(&self, input: &str) -> bool {
+        self.label == input
+    }
+
+    // the input never gets sliced, so accented letters of several bytes are compared whole
+    pub fn is_prefixed_by(&self, input: &str) -> bool {
+        !input.is_empty() && self.label.starts_with(input)
+    }
+
+    pub fn default_color(&self) -> Color {
+        self.default_color
+    }
+
+    pub fn set_default_color(&mut self, color: Color) {
+        self.default_color = color;
+    }
+
+    pub fn typed_color(&self) -> Color {
+        self.typed_color
+    }
+
+    pub fn set_typed_color(&mut self, color: Color) {
+        self.typed_color = color;
+    }
+
+    // moves the word and returns true only on the frame it escapes
+    pub fn update(&mut self, seconds: f32, screen_width: f32, width: f32) -> bool {
         self.pos.x += self.velocity.x * seconds;
         self.pos.y += self.velocity.y * seconds;
 
         self.real_pos.x += self.velocity.x * seconds;
         self.real_pos.y += self.velocity.y * seconds;
+
+        if self.is_shrinking && !self.is_typed {
+            self.shrink_timer += seconds;
+            if self.shrink_timer >= Word::SHRINK_INTERVAL {
+                self.shrink_timer -= Word::SHRINK_INTERVAL;
+                self.shrink();
+            }
+        }
+
+        if self.escaped || !self.has_escaped(width, screen_width - Word::ESCAPE_MARGIN) {
+            return false;
+        }
+
+        self.escaped = true;
+        true
+    }
+
+    // keeps the position and the speed, the sprite is measured again right away
+    pub fn set_label(&mut self, ctx: &mut Context, new_label: &str) {
+        self.replace_label(new_label);
+        self.size = Some(Vector2 { x: self.sprite.width(ctx), y: self.sprite.height(ctx) });
+    }
+
+    // same as `set_label`, the size is measured again on the next bounding rect
+    pub fn replace_label(&mut self, new_label: &str) {
+        self.label = String::from(new_label);
+        self.sprite.set_text(&draw_helper::visual_order(&self.label));
+        self.size = None;
+    }
+
+    fn shrink(&mut self) {
+        if self.label.chars().count() <= Word::MIN_SHRINK_LEN {
+            return;
+        }
+
+        let mut shorter = self.label.clone();
+        shorter.pop();
+        self.replace_label(&shorter);
     }
 
     pub fn translate(&mut self, translation: Point2<f32>) {
@@ -56,33 +185,97 @@ impl Word {
         self.pos.y = self.real_pos.y;
     }
 
-    pub fn get_reward(&mut self) -> f32 {
-        let color_multi = {
-            if self.is_color_changing {
-                2.0;
-            }
+    // moves the word back towards the left edge, never past it, returns how far it went
+    pub fn pull_back(&mut self, distance: f32) -> f32 {
+        let pulled = distance.clamp(0.0, self.real_pos.x.max(0.0));
+        self.pos.x -= pulled;
+        self.real_pos.x -= pulled;
+        pulled
+    }
 
-            1.0
-        };
+    // typed, removed by a buff or escaped, either way it leaves the screen
+    pub fn is_expired(&self) -> bool {
+        self.is_typed || self.escaped
+    }
+
+    // the word escapes once its right edge reaches the deadline
+    pub fn has_escaped(&self, width: f32, deadline: f32) -> bool {
+        self.pos.x + width >= deadline
+    }
 
-        self.velocity.x * color_multi * (self.label.len() as f32) / 100.0
+    // seconds until the word escapes at its current speed, same edge as `update` uses
+    pub fn time_to_escape(&self, screen_width: f32, width: f32) -> Option<f32> {
+        time_to_escape(screen_width - Word::ESCAPE_MARGIN - (self.pos.x + width), self.velocity.x)
+    }
+
+    pub fn get_reward(&self, economy: &Economy) -> f32 {
+        economy.reward(self.velocity.x, &self.label, self.is_color_changing)
     }
 
     pub fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        if self.is_color_changing {
-            self.sprite.draw(self.pos,
-                       graphics::Color::from_rgb(
-                                self.rng.gen_range(0 ..= 255),
-                                self.rng.gen_range(0 ..= 255),
-                                self.rng.gen_range(0 ..= 255)), ctx)
+        let color = self.current_color();
+        let mut underline = RectBatch::new();
+        self.add_bonus_underline(ctx, color, &mut underline);
+        underline.draw(ctx)?;
+        self.sprite.draw(self.pos, color, ctx)
+    }
+
+    // batched with the other words, the text shows up with the next `graphics::draw_queued_text`
+    // and the underline with the next draw of `shapes`
+    pub fn queue(&mut self, ctx: &mut Context, shapes: &mut RectBatch) -> GameResult<()> {
+        let color = self.current_color();
+        self.add_bonus_underline(ctx, color, shapes);
+        self.sprite.queue(self.pos, color, ctx)
+    }
+
+    // the bonus of a color changing word stays visible with the animation turned off
+    pub fn shows_bonus_underline(&self) -> bool {
+        self.is_color_changing && self.is_color_frozen && !self.is_typed
+    }
+
+    fn add_bonus_underline(&self, ctx: &mut Context, color: Color, shapes: &mut RectBatch) {
+        if !self.shows_bonus_underline() {
+            return;
         }
-        else {
-            self.sprite.draw(self.pos, graphics::Color::from_rgb(255, 255, 255), ctx)
+
+        let rect = self.bounding_rect(ctx);
+        let underline = graphics::Rect::new(rect.x, rect.bottom() - Word::BONUS_UNDERLINE_HEIGHT, rect.w, Word::BONUS_UNDERLINE_HEIGHT);
+        shapes.add(underline, color);
+    }
+
+    // color changing words roll a new color every frame, unless they are frozen
+    fn current_color(&mut self) -> Color {
+        let mut color = {
+            if self.is_targeted {
+                self.typed_color
+            }
+            else if self.is_color_changing && self.is_color_frozen {
+                Word::FROZEN_BONUS_COLOR
+            }
+            else if self.is_color_changing {
+                graphics::Color::from_rgb(
+                    self.rng.gen_range(0 ..= 255),
+                    self.rng.gen_range(0 ..= 255),
+                    self.rng.gen_range(0 ..= 255))
+            }
+            else {
+                self.default_color
+            }
+        };
+
+        if self.is_dimmed {
+            color.a *= Word::DIMMED_ALPHA;
         }
+
+        color
     }
 
     // display sprite boundaries (for debug purposes)
     pub fn bounding_rect(&self, ctx: &mut Context) -> graphics::Rect {
+        if let Some(rect) = self.cached_rect() {
+            return rect;
+        }
+
         let left = self.pos.x;
         let right = self.pos.x + self.sprite.width(ctx);
         let top = self.pos.y;
@@ -90,4 +283,84 @@ impl Word {
 
         graphics::Rect::new(left, top, right - left, bottom - top)
     }
+
+    // only known for the words created with a size
+    pub fn cached_rect(&self) -> Option<graphics::Rect> {
+        self.size.map(|size| graphics::Rect::new(self.pos.x, self.pos.y, size.x, size.y))
+    }
+}
+
+// none for a word that doesn't move towards the edge, 0 once it's past it
+pub fn time_to_escape(remaining_distance: f32, speed: f32) -> Option<f32> {
+    if speed.is_nan() || speed <= 0.0 || !remaining_distance.is_finite() {
+        return None;
+    }
+
+    Some(remaining_distance.max(0.0) / speed)
+}
+
+// index of the word closest to escaping, the one furthest to the right
+// the word the input finishes, in order only the oldest word can be typed, like the words of a passage
+pub fn matching_word(words: &[Word], input: &str, in_order: bool) -> Option<usize> {
+    let in_turn = if in_order { words.len().min(1) } else { words.len() };
+    words[.. in_turn].iter().position(|word| word.matches(input))
+}
+
+pub fn most_urgent(words: &[Word]) -> Option<usize> {
+    words.iter()
+        .enumerate()
+        .filter(|(_, word)| !word.is_expired())
+        .max_by(|(_, a), (_, b)| a.pos.x.total_cmp(&b.pos.x))
+        .map(|(index, _)| index)
+}
+
+// the magnet buff, pulls the word closest to escaping back by `distance`
+// returns its index and how far it went, none when there is no word left to pull
+pub fn pull_back_most_urgent(words: &mut [Word], distance: f32) -> Option<(usize, f32)> {
+    let index = most_urgent(words)?;
+    let pulled = words[index].pull_back(distance);
+
+    Some((index, pulled))
+}
+
+// the points of a typed word, floating up from where it was and fading out
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointPopup {
+    pub text: String,
+    pub pos: Point2<f32>,
+    pub color: Color,
+    velocity: Vector2<f32>,
+    lifetime: f32,
+    time_left: f32
+}
+
+impl PointPopup {
+    pub const LIFETIME: f32 = 1.0;
+    pub const RISE_SPEED: f32 = 60.0;
+
+    pub fn new(points: f32, pos: Point2<f32>, color: Color) -> Self {
+        PointPopup {
+            text: format!("{:+.2}", points),
+            pos,
+            color,
+            velocity: Vector2 { x: 0.0, y: -PointPopup::RISE_SPEED },
+            lifetime: PointPopup::LIFETIME,
+            time_left: PointPopup::LIFETIME
+        }
+    }
+
+    pub fn update(&mut self, seconds: f32) {
+        self.pos.x += self.velocity.x * seconds;
+        self.pos.y += self.velocity.y * seconds;
+        self.time_left = (self.time_left - seconds).max(0.0);
+    }
+
+    // fully opaque when it spawns, gone once its time is up
+    pub fn alpha(&self) -> f32 {
+        self.time_left / self.lifetime
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_left <= 0.0
+    }
 }
\ No newline at end of file
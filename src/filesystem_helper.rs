@@ -1,9 +1,16 @@
-use ggez:: { filesystem, Context };
+use ggez:: { filesystem, graphics, Context, GameResult };
 
-use std::io::{Read, Write};
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
 use std::str;
+use std::fs;
+use std::path::{ Path, PathBuf };
 use std::mem::swap;
 
+use crate::replay::Recording;
+use crate::session::SessionSnapshot;
+use crate::stats::RunReport;
+
 pub fn read_file_by_lines(ctx: &Context, path: &str) -> Vec<String> {
     let file = filesystem::open(ctx, path);
         
@@ -22,7 +29,82 @@ pub fn read_file_by_lines(ctx: &Context, path: &str) -> Vec<String> {
     words.iter().map(|x| x.to_string()).collect::<Vec<String>>()
 }
 
-pub fn save_score(ctx: &Context, username: String, score: f32, scoreboard_size: usize) -> Vec<String> {
+// the dev resources from `cargo run` come first, then the ones shipped next to the binary
+pub fn resource_dirs(manifest_dir: Option<&str>, exe_path: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(manifest_dir) = manifest_dir {
+        dirs.push(PathBuf::from(manifest_dir).join("resources"));
+    }
+
+    if let Some(exe_dir) = exe_path.and_then(|path| path.parent()) {
+        dirs.push(exe_dir.join("resources"));
+    }
+
+    dirs
+}
+
+pub const COLOR_HEADER: &str = "#color";
+
+// an optional "#color <r> <g> <b>" first line gives the words of a dictionary their base color
+// the header is dropped from the lines even when its color can't be read
+pub fn split_color_header(mut lines: Vec<String>) -> (Option<graphics::Color>, Vec<String>) {
+    let header = match lines.first() {
+        Some(line) if line.starts_with(COLOR_HEADER) => lines.remove(0),
+        _ => return (None, lines)
+    };
+
+    let channels = header[COLOR_HEADER.len() ..].split_whitespace().map(|channel| channel.parse::<u8>()).collect::<Vec<_>>();
+    let color = match channels.as_slice() {
+        [Ok(r), Ok(g), Ok(b)] => Some(graphics::Color::from_rgb(*r, *g, *b)),
+        _ => None
+    };
+
+    (color, lines)
+}
+
+// keeps the first occurrence of every line, returns the kept lines and how many were dropped
+pub fn dedup_lines(lines: Vec<String>) -> (Vec<String>, usize) {
+    let total = lines.len();
+    let mut seen = HashSet::new();
+    let unique = lines.into_iter().filter(|line| seen.insert(line.clone())).collect::<Vec<String>>();
+    let removed = total - unique.len();
+
+    (unique, removed)
+}
+
+// drops the lines longer than `max_len` characters, returns the kept lines and how many were dropped
+pub fn filter_by_length(lines: Vec<String>, max_len: usize) -> (Vec<String>, usize) {
+    let total = lines.len();
+    let kept = lines.into_iter().filter(|line| line.chars().count() <= max_len).collect::<Vec<String>>();
+    let removed = total - kept.len();
+
+    (kept, removed)
+}
+
+pub fn load_scores(ctx: &Context) -> Vec<String> {
+    if filesystem::exists(ctx, "/scoring.data") {
+        read_file_by_lines(ctx, "/scoring.data")
+    }
+    else {
+        Vec::new()
+    }
+}
+
+// the score ends every "<name> <score>" line, the names can have spaces in them
+pub fn score_values(lines: &[String]) -> Vec<f32> {
+    lines.iter()
+        .filter_map(|line| line.split_whitespace().last())
+        .filter_map(|score| score.parse::<f32>().ok())
+        .collect()
+}
+
+// unranked runs only get to see the scoreboard
+pub fn save_score(ctx: &Context, username: String, score: f32, scoreboard_size: usize, ranked: bool) -> GameResult<Vec<String>> {
+    if !ranked {
+        return Ok(load_scores(ctx));
+    }
+
     let mut file;
     if filesystem::exists(ctx, "/scoring.data") {
         let mut scores = read_file_by_lines(ctx, "/scoring.data");
@@ -48,20 +130,74 @@ pub fn save_score(ctx: &Context, username: String, score: f32, scoreboard_size:
             scores.push(new_line);
         }
 
-        file = filesystem::create(ctx, "/scoring.data").unwrap();
+        file = filesystem::create(ctx, "/scoring.data")?;
 
         let _ = file.write(scores.join("\n").as_bytes());
 
-        return scores;
+        return Ok(scores);
     }
     else {
-        file = filesystem::create(ctx, "/scoring.data").unwrap();
+        file = filesystem::create(ctx, "/scoring.data")?;
     }
 
     let new_score = format!("{} {:.2}", username, score);
     let _ = file.write(new_score.as_bytes());
-    let mut result = Vec::new();
-    result.push(new_score);
+    Ok(vec![new_score])
+}
+
+// written to the user data dir, next to the game files rather than the config ones
+pub fn save_run_report(ctx: &Context, report: &RunReport) -> io::Result<()> {
+    let dir = filesystem::user_data_dir(ctx);
+    fs::create_dir_all(dir)?;
+
+    let json = serde_json::to_string_pretty(report)?;
+    fs::write(dir.join("run_report.json"), json)
+}
+
+pub fn save_keystroke_log(ctx: &Context, csv: &str) -> io::Result<()> {
+    let dir = filesystem::user_data_dir(ctx);
+    fs::create_dir_all(dir)?;
+
+    fs::write(dir.join("keystrokes.csv"), csv)
+}
+
+pub fn save_replay(ctx: &Context, recording: &Recording) -> io::Result<()> {
+    let dir = filesystem::user_data_dir(ctx);
+    fs::create_dir_all(dir)?;
+
+    fs::write(dir.join(Recording::FILE_NAME), recording.to_json()?)
+}
 
-    result
+pub fn load_replay(ctx: &Context) -> io::Result<Recording> {
+    let json = fs::read_to_string(filesystem::user_data_dir(ctx).join(Recording::FILE_NAME))?;
+
+    Ok(Recording::from_json(&json)?)
+}
+
+pub fn save_session(ctx: &Context, snapshot: &SessionSnapshot) -> io::Result<()> {
+    let dir = filesystem::user_data_dir(ctx);
+    fs::create_dir_all(dir)?;
+
+    fs::write(dir.join(SessionSnapshot::FILE_NAME), snapshot.to_json()?)
+}
+
+// a save that can't be resumed is deleted right away
+pub fn load_session(ctx: &Context) -> Option<SessionSnapshot> {
+    let path = filesystem::user_data_dir(ctx).join(SessionSnapshot::FILE_NAME);
+    let json = fs::read_to_string(&path).ok()?;
+
+    let snapshot = SessionSnapshot::from_json(&json);
+    if snapshot.is_none() {
+        eprintln!("Discarding the saved session, it's from another version of the game");
+        let _ = fs::remove_file(&path);
+    }
+
+    snapshot
+}
+
+pub fn discard_session(ctx: &Context) {
+    let path = filesystem::user_data_dir(ctx).join(SessionSnapshot::FILE_NAME);
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
 }
\ No newline at end of file
@@ -2,4 +2,15 @@ pub mod debug;
 pub mod assets;
 pub mod entities;
 pub mod draw_helper;
-pub mod filesystem_helper;
\ No newline at end of file
+pub mod filesystem_helper;
+pub mod settings;
+pub mod menu;
+pub mod stats;
+pub mod difficulty;
+pub mod economy;
+pub mod passage;
+pub mod power_up;
+pub mod cli;
+pub mod replay;
+pub mod practice;
+pub mod session;
\ No newline at end of file
@@ -1,42 +1,75 @@
-use ggez::audio::SoundSource;
-use ggez::conf::{ Conf, WindowMode };
+use ggez::conf::{ Conf, FullscreenType, WindowMode };
 use ggez::{ event, timer, filesystem, graphics };
-use ggez::{ Context, ContextBuilder, GameResult };
-use ggez::input::keyboard::is_key_pressed;
-use ggez::mint::Point2;
-use rand::{ Rng, seq };
-use rand::rngs::ThreadRng;
+use ggez::input::keyboard;
+use ggez::{ Context, ContextBuilder, GameError, GameResult };
+use ggez::mint::{ Point2, Vector2 };
+use rand::{ Rng, SeedableRng };
+use rand::rngs::{ StdRng, ThreadRng };
 
 use type_racer::assets::{ Assets, TextSprite, Sprite };
-use type_racer::entities::Word;
+use type_racer::entities::{ self, PointPopup, Word };
+use type_racer::cli;
 use type_racer::debug;
-use type_racer::draw_helper;
+use type_racer::draw_helper::{ self, RectBatch };
 use type_racer::filesystem_helper;
-
-use std::str;
+use type_racer::settings::Settings;
+use type_racer::menu;
+use type_racer::stats::{ self, RunStats };
+use type_racer::difficulty::{ self, Difficulty };
+use type_racer::economy::{ Economy, Wallet };
+use type_racer::passage::Passage;
+use type_racer::practice::{ self, MissedWords };
+use type_racer::power_up::{ self, EscapeCost, PowerUp, PurchaseCounts, PurchaseThrottle };
+use type_racer::replay::{ Playback, Recording, ReplayEvent };
+use type_racer::session::{ SessionSnapshot, WordSnapshot };
+
+use std::collections::VecDeque;
+use std::io::Read;
 use std::env;
-use std::path;
+use std::process;
 
 fn main() {
-    let conf = Conf::new()
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let (width, height) = cli::parse_window_size(&args).unwrap_or_else(|error| {
+        eprintln!("{}, using the default window size", error);
+        cli::DEFAULT_WINDOW_SIZE
+    });
+
+    let mut conf = Conf::new()
     .window_mode(WindowMode {
-        width: 1200.0,
-        height: 1000.0,
+        width,
+        height,
+        resizable: true,
         ..Default::default()
     });
 
-    let (mut ctx, event_loop) = ContextBuilder::new("type_racer", "George Shavov")
-        .default_conf(conf.clone())
-        .build()
-        .unwrap();
+    // machines without an audio device still get to play, in silence
+    let (mut ctx, event_loop) = match ContextBuilder::new("type_racer", "George Shavov").default_conf(conf.clone()).build() {
+        Ok(built) => built,
+        Err(GameError::AudioError(error)) => {
+            eprintln!("Couldn't initialize the audio, playing without sound: {}", error);
+            conf.modules.audio = false;
+            ContextBuilder::new("type_racer", "George Shavov").default_conf(conf.clone()).build().unwrap()
+        },
+        Err(error) => panic!("Couldn't create the game context: {}", error)
+    };
 
     graphics::set_window_title(&ctx, "Type Racer");
 
-    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
-        let mut path = path::PathBuf::from(manifest_dir);
-        
-        path.push("resources");
-        filesystem::mount(&mut ctx, &path, true);
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").ok();
+    let exe_path = env::current_exe().ok();
+    let resource_dirs = filesystem_helper::resource_dirs(manifest_dir.as_deref(), exe_path.as_deref());
+    for dir in resource_dirs.iter() {
+        filesystem::mount(&mut ctx, dir, true);
+    }
+
+    if !filesystem::exists(&ctx, "/words.dict") {
+        eprintln!("Couldn't find words.dict, looked in:");
+        for dir in resource_dirs.iter() {
+            eprintln!("  {}", dir.display());
+        }
+
+        process::exit(1);
     }
 
     let state = MainState::new(&mut ctx, &conf).unwrap();
@@ -44,120 +77,1682 @@ fn main() {
     event::run(ctx, event_loop, state);
 }
 
+#[derive(Debug, PartialEq)]
+enum Phase {
+    Menu,
+    Playing,
+    Paused,
+    GameOver
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MenuItem {
+    ResumeSession,
+    Play,
+    WatchReplay,
+    ColorChangingWords,
+    SurvivalSummary,
+    NextWordPreview,
+    HeartLifes,
+    ShowKps,
+    BreathingBackground,
+    MusicTrack,
+    AdaptiveSpawn,
+    DoubleSpeed,
+    SpeedRamp,
+    ShrinkingWords,
+    PracticeWeakWords,
+    Passage,
+    Fullscreen,
+    StartingCash,
+    Difficulty,
+    Quit,
+    Resume,
+    Restart,
+    QuitToMenu
+}
+
+// the first item is only offered while there's a saved session
+const MAIN_MENU: [MenuItem; 20] = [
+    MenuItem::ResumeSession,
+    MenuItem::Play,
+    MenuItem::WatchReplay,
+    MenuItem::Passage,
+    MenuItem::Difficulty,
+    MenuItem::AdaptiveSpawn,
+    MenuItem::DoubleSpeed,
+    MenuItem::SpeedRamp,
+    MenuItem::ShrinkingWords,
+    MenuItem::PracticeWeakWords,
+    MenuItem::StartingCash,
+    MenuItem::ColorChangingWords,
+    MenuItem::NextWordPreview,
+    MenuItem::HeartLifes,
+    MenuItem::ShowKps,
+    MenuItem::BreathingBackground,
+    MenuItem::SurvivalSummary,
+    MenuItem::MusicTrack,
+    MenuItem::Fullscreen,
+    MenuItem::Quit
+];
+const PAUSE_MENU: [MenuItem; 3] = [MenuItem::Resume, MenuItem::Restart, MenuItem::QuitToMenu];
+
 struct MainState {
     rng: ThreadRng,
     assets: Assets,
     info_panel: TextSprite,
+    settings: Settings,
     sound_volume: f32,
+    // the last of `VOLUME_PRESETS` picked with the volume key
+    volume_preset: usize,
     show_info: bool,
-    game_over: bool,
+    // flipped by every caps lock press, the lock state before the game started isn't known
+    caps_lock: bool,
+    phase: Phase,
+    selected_index: usize,
     saved_score: bool,
+    // off for debug runs and cleared once pasted input finishes a word, the score is kept off the scoreboard
+    ranked: bool,
     current_input: String,
-    cash: f32,
+    // typed since the last update
+    chars_this_frame: usize,
+    // the current input arrived in a burst
+    input_burst: bool,
+    // loops over two blinks, the caret is shown during the first one
+    cursor_timer: f32,
+    economy: Economy,
+    wallet: Wallet,
     score: f32,
+    // words typed in quick succession
+    combo: u32,
+    time_since_typed_word: f32,
     remaining_lifes: u32,
+    // absorbs the next escape
+    shield_active: bool,
+    // picked from the scores saved before this launch, used with the auto difficulty on
+    auto_tuning: difficulty::AutoTuning,
+    // escapes are free until it runs out
+    warmup_time: f32,
+    // bought with the panic button, forgives the escapes like the warmup
+    invulnerable_time: f32,
+    // the word that took the last life
+    killer_word: Option<String>,
+    stats: RunStats,
     words: Vec<Word>,
+    point_popups: Vec<PointPopup>,
     time_until_next_word: f32,
+    next_word: Option<String>,
+    // set while a passage is typed instead of the dictionary words
+    passage: Option<Passage>,
     game_speed_up: f32,
     time_until_shake: f32,
     shake_screen: bool,
     shake_time: f32,
+    // false until the first keystroke of a safe start run
+    started: bool,
+    // number of the current or the upcoming wave, with the waves turned on
+    wave: u32,
+    wave_spawns_left: usize,
+    // the rest before the next wave, its banner is shown meanwhile
+    wave_rest: f32,
+    wave_escaped: bool,
+    // words spawned this run, the first ones get the grace
+    spawned_words: u32,
+    toast_label: Option<String>,
+    toast_time: f32,
+    flash_time: f32,
+    breath_time: f32,
+    sudden_death: bool,
+    music_slowed: bool,
+    music_fade_time: f32,
+    // run clock times of the recent keystrokes
+    keystroke_times: VecDeque<f32>,
+    // newest first, shown in the side panel
+    typed_log: VecDeque<String>,
+    // only filled when the keystroke log setting is on
+    keystroke_log: Vec<(f32, char)>,
+    recording: Recording,
+    // set while a recorded run is played back instead of the player's input
+    playback: Option<Playback>,
+    // the run that was interrupted by quitting in the last launch
+    saved_session: Option<SessionSnapshot>,
+    // the run was restored from a saved session, so its recording misses the beginning
+    resumed: bool,
     screen_width: f32,
     screen_height: f32,
+    // vertical range of the spawned words, follows the window size
+    spawn_band: (f32, f32),
     words_pool: Vec<String>,
+    // escaped words of all the runs, fed back in by the practice mode
+    missed_words: MissedWords,
+    // from the dictionary header, passage words keep the default one
+    words_color: graphics::Color,
+    passages: Vec<String>,
     scoreboard: Vec<String>,
-    power_up_panels: Vec<TextSprite>
+    // drawn right to left, in `POWER_UP_ORDER`
+    power_up_panels: Vec<TextSprite>,
+    // the way back of the last magnet pull and the seconds it's still shown
+    magnet_trail: Option<(graphics::Rect, f32)>,
+    // buys of every power up this run
+    purchase_counts: PurchaseCounts,
+    purchase_throttle: PurchaseThrottle,
+    // only recorded when debug is active
+    frame_times: debug::FrameTimes,
+    // where each power up panel was last drawn, none while it's hidden
+    power_up_rects: [Option<graphics::Rect>; 5],
+    // the latest purchase and the seconds left to undo it
+    last_purchase: Option<(PowerUp, f32)>,
+    speed_up_before_purchase: f32,
+    // left of a timed slow spawn and the speed up it took away
+    slow_spawn_time: f32,
+    slow_spawn_taken: f32,
+    // words taken by the latest removal, with their index and reward, so they can come back
+    removed_words: Vec<(usize, Word, f32)>,
+    // seeded with the seed of the recording, a replay removes the same words as the run
+    removal_rng: StdRng
 }
 
 impl MainState {
-    const BUY_LIFE_TAX: f32 = 300.0;
-    const REMOVE_WORDS_TAX: f32 = 350.0;
-    const SLOW_WORD_SPAWN_TAX: f32 = 1000.0;
-    const REMOVE_WORDS_COUNT: usize = 2;
     const INITAL_SOUND_VOLUME: f32 = 0.05;
     const SOUND_VOLUME_STEP: f32 = 0.005;
+    const VOLUME_PRESETS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
     const SCOREBOARD_SIZE: usize = 10;
+    const DEFAULT_USERNAME: &'static str = "player";
     const TOP_PANEL_TEXT_SIZE: f32 = 34.0;
     const BOT_PANEL_TEXT_SIZE: f32 = 40.0;
     const CENTER_PANEL_TEXT_SIZE: f32 = 40.0;
+    const WORD_TEXT_SIZE: f32 = 32.0;
+    // longer words get a smaller font rather than running off the screen
+    const MAX_WORD_WIDTH_SHARE: f32 = 0.5;
+    const HEART_SPACING: f32 = 4.0;
+    const MENU_TITLE_TEXT_SIZE: f32 = 80.0;
+    const MENU_ITEM_TEXT_SIZE: f32 = 40.0;
+    const MENU_ITEM_SPACING: f32 = 16.0;
     const SHAKE_DURATION: f32 = 1.0;
     const SHAKE_MAGNITUDE: f32 = 3.0;
+    const STARTING_LIFES: u32 = 5;
+    const FIRST_WORD_DELAY: f32 = 3.0;
+    const WAVE_REST: f32 = 4.0;
+    const FIRST_SHAKE_DELAY: f32 = 10.0;
+    const MILESTONE_INTERVAL: u32 = 25;
+    const TOAST_DURATION: f32 = 2.0;
+    const FLASH_DURATION: f32 = 0.3;
+    const MISS_POPUP_X: f32 = 20.0;
+    const POPUP_TEXT_SIZE: f32 = 26.0;
+    const BUFF_PROGRESS_BAR_HEIGHT: f32 = 4.0;
+    const HISTOGRAM_BAR_WIDTH: f32 = 10.0;
+    const HISTOGRAM_HEIGHT: f32 = 80.0;
+    const MINIMAP_HEIGHT: f32 = 6.0;
+    const TYPED_LOG_WIDTH: f32 = 200.0;
+    const TYPED_LOG_LEN: usize = 10;
+    const MINIMAP_TICK_WIDTH: f32 = 3.0;
+    const ESCAPE_TIME_TEXT_SIZE: f32 = 20.0;
+    const SPEEDRUN_TEXT_SIZE: f32 = 28.0;
+    const POWER_UP_ORDER: [PowerUp; 5] = [PowerUp::SlowSpawn, PowerUp::RemoveWords, PowerUp::ExtraLife, PowerUp::Shield, PowerUp::Magnet];
+    // share of the escape line the magnet pulls the word back
+    const MAGNET_PULL_SHARE: f32 = 0.4;
+    const MAGNET_TRAIL_DURATION: f32 = 0.3;
+    const BREATH_PERIOD: f32 = 4.0;
+    // percent of the spawned words that shrink, when the setting is on
+    const SHRINKING_WORD_CHANCE: u8 = 25;
+    // dark enough for the white words to stay readable
+    const BREATH_BRIGHTNESS: f32 = 0.12;
+    const SUDDEN_DEATH_MUSIC_PITCH: f32 = 1.2;
+    const GAME_OVER_MUSIC_PITCH: f32 = 0.8;
+    // the music fades down to this share of the sound volume
+    const GAME_OVER_MUSIC_VOLUME: f32 = 0.3;
+    const MUSIC_FADE_DURATION: f32 = 2.0;
+    const KPS_WINDOW: f32 = 1.0;
+    const CURSOR_BLINK: f32 = 0.5;
+    const UNDO_WINDOW: f32 = 1.5;
+    const COMBO_BAR_WIDTH: f32 = 200.0;
+    // frames summed up in every frame time log line
+    const FRAME_TIMES_WINDOW: usize = 120;
 
     fn new(ctx: &mut Context, conf: &Conf) -> GameResult<MainState> {
-        let mut assets = Assets::new(ctx)?;
+        let settings = Settings::load(ctx);
+        let mut assets = Assets::new(ctx, &settings.music_track, conf.modules.audio)?;
         assets.background_music.set_volume(MainState::INITAL_SOUND_VOLUME);
         let _ = assets.background_music.play(ctx);
-        let words = filesystem_helper::read_file_by_lines(ctx, "/words.dict");
-
-        let info_panel_label = format!(
-"(+) to volume up
-(-) to volume down
-
-Buffs become visible when you have the required cash:
-(1) for extra life  ({:.2}$)
-(2) for words removal  ({:.2}$)
-(3) for slow words spawn  ({:.2}$)
-
-(Esc) to quit",
-                           MainState::BUY_LIFE_TAX,
-                           MainState::REMOVE_WORDS_TAX,
-                           MainState::SLOW_WORD_SPAWN_TAX);
-        let info_panel = TextSprite::new(&info_panel_label, ctx, MainState::CENTER_PANEL_TEXT_SIZE)?;
-
-        let slow_word_spawn_label = format!("(3) Slow spawn ({:.2}$)", MainState::SLOW_WORD_SPAWN_TAX);
-        let slow_word_spawn_panel = TextSprite::new(&slow_word_spawn_label, ctx, MainState::TOP_PANEL_TEXT_SIZE)?;
-
-        let remove_words_label = format!("(2) Remove {} words ({:.2}$)",MainState::REMOVE_WORDS_COUNT , MainState::REMOVE_WORDS_TAX);
-        let remove_words_panel = TextSprite::new(&remove_words_label, ctx, MainState::TOP_PANEL_TEXT_SIZE)?;
-
-        let extra_life_label = format!("(1) extra life ({:.2}$)", MainState::BUY_LIFE_TAX);
-        let extra_life_panel = TextSprite::new(&extra_life_label, ctx, MainState::TOP_PANEL_TEXT_SIZE)?;
+        let (words_color, mut words) = filesystem_helper::split_color_header(filesystem_helper::read_file_by_lines(ctx, "/words.dict"));
+        let words_color = words_color.unwrap_or(Word::DEFAULT_COLOR);
+        if settings.dedup_words {
+            let (unique, removed) = filesystem_helper::dedup_lines(words);
+            eprintln!("Removed {} duplicate words from the dictionary", removed);
+            words = unique;
+        }
 
-        let mut power_up_panels = Vec::new();
-        power_up_panels.push(slow_word_spawn_panel);
-        power_up_panels.push(remove_words_panel);
-        power_up_panels.push(extra_life_panel);
+        if settings.max_word_len > 0 {
+            let (kept, removed) = filesystem_helper::filter_by_length(words.clone(), settings.max_word_len);
+            if kept.is_empty() {
+                eprintln!("No word is {} characters or shorter, keeping the whole dictionary", settings.max_word_len);
+            }
+            else {
+                eprintln!("Excluded {} words longer than {} characters", removed, settings.max_word_len);
+                words = kept;
+            }
+        }
 
-        let start_state = MainState {
+        let auto_tuning = difficulty::auto_tuning(&filesystem_helper::score_values(&filesystem_helper::load_scores(ctx)), MainState::STARTING_LIFES);
+        if settings.auto_difficulty {
+            eprintln!("Auto difficulty picked {} with {} lifes", auto_tuning.difficulty, auto_tuning.starting_lifes);
+        }
+        let stats = RunStats::new(settings.difficulty);
+        let saved_session = if settings.resume_session { filesystem_helper::load_session(ctx) } else { None };
+        let starting_cash = settings.starting_cash;
+        let economy = Economy::default();
+        let passages = filesystem::read_dir(ctx, Passage::DIR)
+            .map(|paths| Passage::passages_from(paths.collect()))
+            .unwrap_or_default();
+        let info_panel = MainState::build_info_panel(&settings, &economy, assets.font);
+
+        let power_up_panels = MainState::POWER_UP_ORDER.iter()
+            .map(|power_up| TextSprite::new(&MainState::power_up_label(*power_up, power_up.tax(&economy)), assets.font, MainState::TOP_PANEL_TEXT_SIZE))
+            .collect::<Vec<TextSprite>>();
+
+        let mut start_state = MainState {
             rng: rand::thread_rng(),
-            assets: assets,
+            assets,
             info_panel,
+            settings,
             sound_volume: MainState::INITAL_SOUND_VOLUME,
+            volume_preset: 0,
             show_info: false,
-            game_over: false,
+            caps_lock: false,
+            phase: Phase::Menu,
+            selected_index: 0,
             saved_score: false,
+            ranked: !debug::is_active(),
+            chars_this_frame: 0,
+            input_burst: false,
             current_input: String::new(),
-            cash: 0.0,
+            cursor_timer: 0.0,
+            economy,
+            wallet: Wallet::new(starting_cash),
             score: 0.0,
-            remaining_lifes: 5,
+            combo: 0,
+            time_since_typed_word: f32::MAX,
+            remaining_lifes: MainState::STARTING_LIFES,
+            shield_active: false,
+            auto_tuning,
+            warmup_time: 0.0,
+            invulnerable_time: 0.0,
+            killer_word: None,
+            stats,
             words: Vec::new(),
-            time_until_next_word: 3.0,
+            point_popups: Vec::new(),
+            time_until_next_word: MainState::FIRST_WORD_DELAY,
+            next_word: None,
+            passage: None,
             game_speed_up: 0.0,
-            time_until_shake: 10.0,
+            time_until_shake: MainState::FIRST_SHAKE_DELAY,
             shake_screen: false,
             shake_time: MainState::SHAKE_DURATION,
+            started: true,
+            wave: 1,
+            wave_spawns_left: 0,
+            wave_rest: MainState::WAVE_REST,
+            wave_escaped: false,
+            spawned_words: 0,
+            toast_label: None,
+            toast_time: 0.0,
+            flash_time: 0.0,
+            breath_time: 0.0,
+            sudden_death: false,
+            music_slowed: false,
+            music_fade_time: 0.0,
+            keystroke_times: VecDeque::new(),
+            typed_log: VecDeque::new(),
+            keystroke_log: Vec::new(),
+            recording: Recording::new(),
+            playback: None,
+            saved_session,
+            resumed: false,
             screen_width: conf.window_mode.width,
             screen_height: conf.window_mode.height,
+            spawn_band: (0.0, 1.0),
             words_pool: words,
+            missed_words: MissedWords::load(ctx),
+            words_color,
+            passages,
             scoreboard: Vec::new(),
-            power_up_panels
+            power_up_panels,
+            magnet_trail: None,
+            purchase_counts: PurchaseCounts::default(),
+            purchase_throttle: PurchaseThrottle::new(),
+            frame_times: debug::FrameTimes::new(MainState::FRAME_TIMES_WINDOW),
+            power_up_rects: [None; 5],
+            last_purchase: None,
+            speed_up_before_purchase: 0.0,
+            slow_spawn_time: 0.0,
+            slow_spawn_taken: 0.0,
+            removed_words: Vec::new(),
+            removal_rng: StdRng::seed_from_u64(0)
+        };
+
+        start_state.next_word = start_state.pick_word(None);
+        start_state.spawn_band = start_state.compute_spawn_band(ctx);
+        if start_state.settings.fullscreen {
+            start_state.apply_fullscreen(ctx);
+        }
+
+        Ok(start_state)
+    }
+
+    fn build_info_panel(settings: &Settings, economy: &Economy, font: graphics::Font) -> TextSprite {
+        let color_changing_state = if settings.color_changing_words { "on" } else { "off" };
+        let focus_mode_state = if settings.focus_mode { "on" } else { "off" };
+        let speedrun_hud_state = if settings.speedrun_hud { "on" } else { "off" };
+        let info_panel_label = format!(
+"(+) to volume up
+(-) to volume down
+(F9) to mute, (F10) for full volume
+(F1) color-changing words: {}
+(F2) focus mode: {}
+(F3) speedrun hud: {}
+(F11) fullscreen
+({}) to close this panel
+
+Buffs light up when you have the required cash:
+(1) for extra life  ({:.2}$)
+(2) for words removal  ({:.2}$)
+(3) for slow words spawn  ({:.2}$)
+(4) for a shield against the next escape  ({:.2}$)
+(6) to pull the word closest to escaping back  ({:.2}$)
+(0) to undo a purchase in the next {:.1} seconds
+
+(Esc) to pause",
+                           color_changing_state,
+                           focus_mode_state,
+                           speedrun_hud_state,
+                           settings.info_key,
+                           economy.buy_life_tax,
+                           economy.remove_words_tax,
+                           economy.slow_word_spawn_tax,
+                           economy.shield_tax,
+                           economy.magnet_tax,
+                           MainState::UNDO_WINDOW);
+
+        TextSprite::new(&info_panel_label, font, MainState::CENTER_PANEL_TEXT_SIZE)
+    }
+
+    // measured from the hud text, so the words never cover the labels
+    fn compute_spawn_band(&self, ctx: &mut Context) -> (f32, f32) {
+        const MARGIN: f32 = 10.0;
+
+        let top_hud_height = TextSprite::new("Info|", self.assets.font, MainState::TOP_PANEL_TEXT_SIZE).height(ctx);
+        let bottom_hud_height = TextSprite::new("Input:", self.assets.font, MainState::BOT_PANEL_TEXT_SIZE).height(ctx);
+        let word_height = TextSprite::new("word", self.assets.font, MainState::WORD_TEXT_SIZE).height(ctx);
+
+        draw_helper::spawn_band(self.screen_height, top_hud_height, bottom_hud_height, word_height, MARGIN)
+    }
+
+    fn load_passage(&self, ctx: &mut Context) -> Option<Passage> {
+        if self.settings.passage.is_empty() {
+            return None;
+        }
+
+        let mut text = String::new();
+        let read = filesystem::open(ctx, &self.settings.passage).and_then(|mut file| Ok(file.read_to_string(&mut text)?));
+        match read {
+            Ok(_) => Some(Passage::from_text(&text)),
+            Err(error) => {
+                eprintln!("Couldn't load the passage {}, playing the dictionary words: {}", self.settings.passage, error);
+                None
+            }
+        }
+    }
+
+    // start a fresh run, keeping settings, volume and the loaded words
+    fn reset(&mut self, ctx: &mut Context) {
+        if self.settings.auto_difficulty {
+            self.settings.difficulty = self.auto_tuning.difficulty;
+        }
+        self.show_info = false;
+        self.saved_score = false;
+        // debug runs can't lose lifes, so they farm as much cash as they like
+        self.ranked = !debug::is_active();
+        self.current_input = String::new();
+        self.chars_this_frame = 0;
+        self.input_burst = false;
+        self.wallet = Wallet::new(self.settings.starting_cash);
+        self.score = 0.0;
+        self.remaining_lifes = self.starting_lifes();
+        self.combo = 0;
+        self.time_since_typed_word = f32::MAX;
+        self.killer_word = None;
+        self.shield_active = false;
+        self.warmup_time = self.settings.difficulty.warmup_duration();
+        self.invulnerable_time = 0.0;
+        self.stats = RunStats::new(self.settings.difficulty);
+        self.words.clear();
+        self.point_popups.clear();
+        self.time_until_next_word = MainState::FIRST_WORD_DELAY;
+        self.passage = self.load_passage(ctx);
+        self.next_word = self.pick_word(None);
+        self.game_speed_up = 0.0;
+        self.time_until_shake = MainState::FIRST_SHAKE_DELAY;
+        self.shake_screen = false;
+        self.shake_time = MainState::SHAKE_DURATION;
+        self.started = !self.settings.safe_start;
+        self.wave = 1;
+        self.wave_spawns_left = 0;
+        self.wave_rest = MainState::WAVE_REST;
+        self.wave_escaped = false;
+        self.spawned_words = 0;
+        self.toast_label = None;
+        self.toast_time = 0.0;
+        self.flash_time = 0.0;
+        self.breath_time = 0.0;
+        self.scoreboard = Vec::new();
+        self.keystroke_times.clear();
+        self.typed_log.clear();
+        self.keystroke_log.clear();
+        self.recording.clear();
+        let removal_seed = rand::random::<u64>();
+        self.recording.set_removal_seed(removal_seed);
+        self.removal_rng = StdRng::seed_from_u64(removal_seed);
+        self.playback = None;
+        self.resumed = false;
+        self.last_purchase = None;
+        self.removed_words.clear();
+        self.purchase_counts = PurchaseCounts::default();
+        self.magnet_trail = None;
+        self.purchase_throttle = PurchaseThrottle::new();
+        self.slow_spawn_time = 0.0;
+        self.slow_spawn_taken = 0.0;
+
+        if self.sudden_death || self.music_slowed {
+            self.sudden_death = false;
+            self.music_slowed = false;
+            self.music_fade_time = 0.0;
+            self.assets.background_music.set_volume(self.sound_volume);
+            self.set_music_pitch(ctx, 1.0);
+        }
+    }
+
+    // passages aren't recorded, the replay runs on the dictionary rules
+    fn watch_replay(&mut self, ctx: &mut Context) {
+        match filesystem_helper::load_replay(ctx) {
+            Ok(recording) => {
+                self.reset(ctx);
+                self.removal_rng = StdRng::seed_from_u64(recording.removal_seed());
+                self.passage = None;
+                self.next_word = None;
+                self.ranked = false;
+                self.playback = Some(Playback::new(recording));
+                self.started = true;
+                self.phase = Phase::Playing;
+            },
+            Err(error) => eprintln!("Couldn't load the last run: {}", error)
+        }
+    }
+
+    // every way out of the game goes through here, ggez only calls quit_event when the window is closed
+    // the score of a finished run gets saved even when the player quits before typing a name, an unfinished run can be kept to resume
+    fn save_before_quit(&mut self, ctx: &mut Context) {
+        self.settings.save(ctx);
+
+        if self.phase == Phase::GameOver && !self.saved_score {
+            let username = if self.current_input.is_empty() { String::from(MainState::DEFAULT_USERNAME) } else { self.current_input.clone() };
+            if let Err(error) = filesystem_helper::save_score(ctx, stats::leaderboard_name(&username, self.settings.double_speed), self.score, MainState::SCOREBOARD_SIZE, self.ranked) {
+                eprintln!("Couldn't save the score before quitting: {}", error);
+            }
+            self.saved_score = true;
+        }
+
+        // passages and replays aren't saved, they start over
+        let in_run = self.phase == Phase::Playing || self.phase == Phase::Paused;
+        if self.settings.resume_session && in_run && self.playback.is_none() && self.passage.is_none() {
+            if let Err(error) = filesystem_helper::save_session(ctx, &self.snapshot()) {
+                eprintln!("Couldn't save the session before quitting: {}", error);
+            }
+        }
+    }
+
+    fn quit(&mut self, ctx: &mut Context) {
+        self.save_before_quit(ctx);
+        event::quit(ctx);
+    }
+
+    fn snapshot(&self) -> SessionSnapshot {
+        let words = self.words.iter()
+            .filter(|word| !word.is_typed)
+            .map(|word| WordSnapshot {
+                label: String::from(word.label()),
+                x: word.pos.x,
+                y: word.pos.y,
+                speed: word.speed(),
+                is_color_changing: word.is_color_changing,
+                is_shrinking: word.is_shrinking
+            })
+            .collect();
+
+        SessionSnapshot {
+            version: SessionSnapshot::VERSION,
+            difficulty: self.settings.difficulty,
+            words,
+            next_word: self.next_word.clone(),
+            cash: self.wallet.cash(),
+            score: self.score,
+            remaining_lifes: self.remaining_lifes,
+            shield_active: self.shield_active,
+            ranked: self.ranked,
+            warmup_time: self.warmup_time,
+            time_until_next_word: self.time_until_next_word,
+            game_speed_up: self.game_speed_up,
+            time_until_shake: self.time_until_shake,
+            slow_spawn_time: self.slow_spawn_time,
+            slow_spawn_taken: self.slow_spawn_taken,
+            wave: self.wave,
+            wave_spawns_left: self.wave_spawns_left,
+            wave_rest: self.wave_rest,
+            wave_escaped: self.wave_escaped,
+            spawned_words: self.spawned_words,
+            purchases: self.purchase_counts,
+            stats: self.stats.clone()
+        }
+    }
+
+    // starts paused, so the player gets a moment before the words move again
+    fn resume_session(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let snapshot = match self.saved_session.take() {
+            Some(snapshot) => snapshot,
+            None => return Ok(())
+        };
+        filesystem_helper::discard_session(ctx);
+
+        self.reset(ctx);
+        self.settings.difficulty = snapshot.difficulty;
+        self.passage = None;
+        self.resumed = true;
+        self.next_word = snapshot.next_word;
+        self.wallet = Wallet::new(snapshot.cash);
+        self.score = snapshot.score;
+        self.remaining_lifes = snapshot.remaining_lifes;
+        self.shield_active = snapshot.shield_active;
+        self.ranked = snapshot.ranked;
+        self.warmup_time = snapshot.warmup_time;
+        self.time_until_next_word = snapshot.time_until_next_word;
+        self.game_speed_up = snapshot.game_speed_up;
+        self.time_until_shake = snapshot.time_until_shake;
+        self.slow_spawn_time = snapshot.slow_spawn_time;
+        self.slow_spawn_taken = snapshot.slow_spawn_taken;
+        self.wave = snapshot.wave;
+        self.wave_spawns_left = snapshot.wave_spawns_left;
+        self.wave_rest = snapshot.wave_rest;
+        self.wave_escaped = snapshot.wave_escaped;
+        self.spawned_words = snapshot.spawned_words;
+        self.purchase_counts = snapshot.purchases;
+        self.stats = snapshot.stats;
+        self.started = true;
+
+        for word in snapshot.words.iter() {
+            let restored = self.build_word(ctx, &word.label, Point2 { x: word.x, y: word.y }, word.speed, word.is_color_changing, word.is_shrinking)?;
+            self.words.push(restored);
+        }
+
+        self.selected_index = 0;
+        self.phase = Phase::Paused;
+        Ok(())
+    }
+
+    fn word_sprite(&self, ctx: &mut Context, label: &str) -> TextSprite {
+        let text = draw_helper::visual_order(label);
+        let word_sprite = TextSprite::new(&text, self.assets.font, MainState::WORD_TEXT_SIZE);
+        let scale = draw_helper::fit_scale(word_sprite.width(ctx), self.screen_width * MainState::MAX_WORD_WIDTH_SHARE);
+        if scale < 1.0 {
+            return TextSprite::new(&text, self.assets.font, MainState::WORD_TEXT_SIZE * scale);
+        }
+
+        word_sprite
+    }
+
+    fn build_word(&self, ctx: &mut Context, label: &str, pos: Point2<f32>, speed: f32, is_color_changing: bool, is_shrinking: bool) -> GameResult<Word> {
+        let word_sprite = Box::new(self.word_sprite(ctx, label));
+        let word_size = Vector2 { x: word_sprite.width(ctx), y: word_sprite.height(ctx) };
+        let mut word = Word::with_size(label, pos, speed, word_sprite, word_size, is_color_changing)?;
+        word.is_shrinking = is_shrinking;
+        if self.passage.is_none() {
+            word.set_default_color(self.words_color);
+        }
+
+        Ok(word)
+    }
+
+    fn spawn_word(&mut self, ctx: &mut Context, label: &str, y: f32, speed: f32, is_color_changing: bool, is_shrinking: bool) -> GameResult<()> {
+        let word = self.build_word(ctx, label, Point2 { x: 0.0, y }, speed, is_color_changing, is_shrinking)?;
+        self.stats.record_spawned(label);
+
+        self.words.push(word);
+        Ok(())
+    }
+
+    // how fast the run earns, to see how far off the expensive buffs are
+    fn cash_per_minute(&self) -> f32 {
+        self.stats.cash_per_minute()
+    }
+
+    fn draw_length_histogram(&self, ctx: &mut Context, bottom: f32) -> GameResult<()> {
+        let histogram = &self.stats.typed_lengths;
+        let max_count = histogram.max_count();
+        if max_count == 0 {
+            return Ok(());
+        }
+
+        let bar_color = graphics::Color::new(0.55, 0.75, 1.0, 0.6);
+        let mut bars = RectBatch::new();
+        for (index, count) in histogram.counts().iter().enumerate() {
+            let height = MainState::HISTOGRAM_HEIGHT * (*count as f32) / (max_count as f32);
+            if height <= 0.0 {
+                continue;
+            }
+
+            let x = 10.0 + index as f32 * (MainState::HISTOGRAM_BAR_WIDTH + 2.0);
+            let bar = graphics::Rect::new(x, bottom - height, MainState::HISTOGRAM_BAR_WIDTH, height);
+            bars.add(bar, bar_color);
+        }
+
+        bars.draw(ctx)
+    }
+
+    // true while the spawns wait, either for the rest to end or for the last words of the wave to go
+    fn advance_wave(&mut self, seconds: f32) -> bool {
+        if !self.settings.waves || self.passage.is_some() || self.playback.is_some() {
+            return false;
+        }
+
+        if self.wave_rest > 0.0 {
+            self.wave_rest -= seconds;
+            if self.wave_rest <= 0.0 {
+                self.wave_rest = 0.0;
+                self.wave_spawns_left = difficulty::wave_size(self.wave);
+                self.time_until_next_word = 0.0;
+            }
+
+            return true;
+        }
+
+        if self.wave_spawns_left > 0 {
+            return false;
+        }
+
+        if self.words.is_empty() {
+            if !self.wave_escaped {
+                let bonus = self.economy.wave_bonus(self.wave);
+                self.add_cash(bonus);
+                self.stats.record_cash(bonus);
+                self.toast_label = Some(format!("Wave {} cleared! +{:.2}$", self.wave, bonus));
+                self.toast_time = MainState::TOAST_DURATION;
+            }
+
+            self.wave += 1;
+            self.wave_rest = MainState::WAVE_REST;
+            self.wave_escaped = false;
+        }
+
+        true
+    }
+
+    fn starting_lifes(&self) -> u32 {
+        if self.settings.auto_difficulty { self.auto_tuning.starting_lifes } else { MainState::STARTING_LIFES }
+    }
+
+    fn next_spawn_gap(&mut self) -> f32 {
+        let auto_spawn_rate = if self.settings.auto_difficulty { self.auto_tuning.spawn_rate } else { 1.0 };
+        let spawn_rate = difficulty::speed_factor(self.settings.double_speed) * auto_spawn_rate;
+        let (min_word_gen_time, max_word_gen_time) = difficulty::scaled_spawn_gap_range(self.game_speed_up, spawn_rate);
+        let gap = self.rng.gen_range(min_word_gen_time .. max_word_gen_time);
+        if self.settings.adaptive_spawn {
+            return gap * difficulty::spawn_gap_scale(self.stats.wpm());
+        }
+
+        gap
+    }
+
+    // the words escape earlier when the typed log takes the right side of the screen
+    fn escape_line(&self) -> f32 {
+        if self.settings.typed_log { self.screen_width - MainState::TYPED_LOG_WIDTH } else { self.screen_width }
+    }
+
+    fn draw_minimap(&self, ctx: &mut Context) -> GameResult<()> {
+        let top = self.screen_height - MainState::MINIMAP_HEIGHT;
+        let strip = graphics::Rect::new(0.0, top, self.screen_width, MainState::MINIMAP_HEIGHT);
+        let mut minimap = RectBatch::new();
+        minimap.add(strip, graphics::Color::new(1.0, 1.0, 1.0, 0.15));
+
+        for word in self.words.iter().filter(|word| !word.is_typed) {
+            let progress = word.progress_ratio(self.escape_line());
+            let tick = graphics::Rect::new(word.pos.x.clamp(0.0, self.screen_width - MainState::MINIMAP_TICK_WIDTH), top, MainState::MINIMAP_TICK_WIDTH, MainState::MINIMAP_HEIGHT);
+            minimap.add(tick, draw_helper::urgency_color(progress));
+        }
+
+        minimap.draw(ctx)
+    }
+
+    fn mode_name(&self) -> &'static str {
+        if self.playback.is_some() {
+            "Replay"
+        }
+        else if self.passage.is_some() {
+            "Passage"
+        }
+        else if self.settings.practice_weak_words {
+            "Practice"
+        }
+        else if self.settings.waves {
+            "Waves"
+        }
+        else if self.settings.survival_summary {
+            "Survival"
+        }
+        else {
+            "Classic"
+        }
+    }
+
+    fn slow_down_music(&mut self, ctx: &mut Context) {
+        self.sudden_death = false;
+        self.music_slowed = true;
+        self.music_fade_time = MainState::MUSIC_FADE_DURATION;
+        self.set_music_pitch(ctx, MainState::GAME_OVER_MUSIC_PITCH);
+    }
+
+    // the pitch is only picked up when the track starts, so the music restarts
+    fn set_music_pitch(&mut self, ctx: &mut Context, pitch: f32) {
+        self.assets.background_music.set_pitch(pitch);
+        let _ = self.assets.background_music.play(ctx);
+    }
+
+    // a passage hands out its words in order and runs dry, the dictionary never does
+    fn pick_word(&mut self, exclude: Option<&str>) -> Option<String> {
+        if let Some(passage) = &mut self.passage {
+            return passage.spawn_next();
+        }
+
+        let exclude = if self.settings.avoid_repeats { exclude } else { None };
+        // without any missed words yet the practice plays the regular dictionary
+        if self.settings.practice_weak_words && difficulty::roll_chance(&mut self.rng, practice::PRACTICE_CHANCE) {
+            if let Some(word) = self.missed_words.pick(&mut self.rng).filter(|word| Some(word.as_str()) != exclude) {
+                return Some(word);
+            }
+        }
+
+        difficulty::pick_word(&mut self.rng, &self.words_pool, exclude)
+    }
+
+    fn toggle_color_changing_words(&mut self, ctx: &mut Context) {
+        self.settings.color_changing_words ^= true;
+        self.settings.save(ctx);
+
+        self.info_panel = MainState::build_info_panel(&self.settings, &self.economy, self.assets.font);
+    }
+
+    // the mute and full volume keys jump to the ends of the presets, the volume key cycles on from there
+    fn set_preset_volume(&mut self, preset: usize) {
+        self.volume_preset = preset;
+        self.sound_volume = MainState::VOLUME_PRESETS[preset];
+        self.assets.background_music.set_volume(self.sound_volume);
+    }
+
+    // every cash change of a run goes through these three, the wallet keeps the cash from going below 0
+    // and adds the change to the delta shown above the cash
+    fn add_cash(&mut self, amount: f32) {
+        self.wallet.add(amount);
+    }
+
+    // false when the power up isn't affordable, the cash is left as it was then
+    fn charge(&mut self, power_up: PowerUp) -> bool {
+        let purchases = self.purchases(power_up);
+        power_up.charge(&self.economy, purchases, &mut self.wallet)
+    }
+
+    // takes as much of the penalty as the cash covers, returns what was taken
+    fn take_cash(&mut self, penalty: f32) -> f32 {
+        self.wallet.take(penalty)
+    }
+
+    // the keys and the panels both buy through here, so a held key buys only once
+    fn press_power_up(&mut self, ctx: &mut Context, power_up: PowerUp, repeat: bool) {
+        if !self.purchase_throttle.press(power_up, self.stats.elapsed, repeat) {
+            return;
+        }
+
+        self.use_power_up(ctx, power_up);
+    }
+
+    // the replay presses the recorded power ups from here, they already got past the throttle once
+    fn use_power_up(&mut self, ctx: &mut Context, power_up: PowerUp) {
+        self.recording.record_power_up(self.stats.elapsed, power_up);
+        match power_up {
+            PowerUp::ExtraLife => self.buy_life(),
+            PowerUp::RemoveWords => self.buy_remove_words(),
+            PowerUp::SlowSpawn => self.buy_slow_spawn(),
+            PowerUp::Shield => self.buy_shield(),
+            PowerUp::Magnet => self.buy_magnet(ctx)
+        }
+    }
+
+    fn buy_life(&mut self) {
+        let purchases = self.purchases(PowerUp::ExtraLife);
+        if power_up::buy_extra_life(&self.economy, purchases, &mut self.wallet, &mut self.remaining_lifes) {
+            self.record_purchase(PowerUp::ExtraLife);
+        }
+    }
+
+    fn buy_remove_words(&mut self) {
+        let purchases = self.purchases(PowerUp::RemoveWords);
+        let removed = power_up::buy_remove_words(&mut self.removal_rng, &self.economy, purchases, &mut self.wallet, &mut self.words, &mut self.stats, self.passage.as_mut());
+        if removed.is_empty() {
+            return;
+        }
+
+        self.record_purchase(PowerUp::RemoveWords);
+        self.score += removed.iter().map(|(_, _, reward)| reward).sum::<f32>();
+        self.removed_words = removed;
+    }
+
+    // there is no ramp to slow down when it's turned off, and one timed slow spawn runs at a time
+    fn buy_slow_spawn(&mut self) {
+        if !self.settings.speed_ramp || self.slow_spawn_time > 0.0 {
+            return;
+        }
+
+        let purchases = self.purchases(PowerUp::SlowSpawn);
+        if let Some(buy) = power_up::buy_slow_spawn(&self.economy, purchases, &mut self.wallet, self.game_speed_up, self.settings.timed_slow_spawn) {
+            self.record_purchase(PowerUp::SlowSpawn);
+            self.speed_up_before_purchase = buy.speed_up_before;
+            self.game_speed_up = buy.speed_up;
+            self.slow_spawn_time = buy.duration;
+            self.slow_spawn_taken = buy.taken;
+        }
+    }
+
+    fn buy_shield(&mut self) {
+        let purchases = self.purchases(PowerUp::Shield);
+        if power_up::buy_shield(&self.economy, purchases, &mut self.wallet, &mut self.shield_active) {
+            self.record_purchase(PowerUp::Shield);
+        }
+    }
+
+    fn panic_available(&self) -> bool {
+        self.settings.panic_min_cash > 0.0 && self.wallet.cash() >= self.settings.panic_min_cash && !self.words.is_empty()
+    }
+
+    // trades the whole cash for an empty board and a few seconds of forgiven escapes, it can't be undone
+    fn press_panic_button(&mut self) {
+        self.recording.record_panic(self.stats.elapsed);
+        if self.settings.panic_min_cash <= 0.0 {
+            return;
+        }
+
+        let panic = match power_up::panic_clear(&mut self.wallet, &mut self.words, self.settings.panic_min_cash) {
+            Some(panic) => panic,
+            None => return
+        };
+
+        for word in panic.cleared.iter() {
+            self.stats.record_removed(word.label());
+            if let Some(passage) = &mut self.passage {
+                passage.finish_word();
+            }
+        }
+
+        // the board the last purchase changed is gone
+        self.last_purchase = None;
+        self.removed_words.clear();
+        self.invulnerable_time = self.invulnerable_time.max(self.economy.panic_invulnerability(panic.spent));
+        self.toast_label = Some(format!("Board cleared for {:.2}$", panic.spent));
+        self.toast_time = MainState::TOAST_DURATION;
+        self.flash_time = MainState::FLASH_DURATION;
+    }
+
+    // a word at the left edge already can't be pulled further
+    fn can_pull_back(&self) -> bool {
+        entities::most_urgent(&self.words).is_some_and(|index| self.words[index].pos.x > 0.0)
+    }
+
+    fn buy_magnet(&mut self, ctx: &mut Context) {
+        if !self.can_pull_back() || !self.charge(PowerUp::Magnet) {
+            return;
+        }
+
+        self.record_purchase(PowerUp::Magnet);
+        let distance = self.escape_line() * MainState::MAGNET_PULL_SHARE;
+        if let Some((index, pulled)) = entities::pull_back_most_urgent(&mut self.words, distance) {
+            let rect = self.words[index].bounding_rect(ctx);
+            let trail = graphics::Rect::new(rect.x, rect.y, rect.w + pulled, rect.h);
+            self.magnet_trail = Some((trail, MainState::MAGNET_TRAIL_DURATION));
+        }
+    }
+
+    // earlier buys of the power up this run, they only raise the price with escalating prices on
+    fn purchases(&self, power_up: PowerUp) -> u32 {
+        if self.settings.escalating_prices { self.purchase_counts.count(power_up) } else { 0 }
+    }
+
+    fn power_up_label(power_up: PowerUp, price: f32) -> String {
+        match power_up {
+            PowerUp::ExtraLife => format!("(1) extra life ({:.2}$)", price),
+            PowerUp::RemoveWords => format!("(2) Remove {} words ({:.2}$)", power_up::REMOVE_WORDS_COUNT, price),
+            PowerUp::SlowSpawn => format!("(3) Slow spawn ({:.2}$)", price),
+            PowerUp::Shield => format!("(4) Shield ({:.2}$)", price),
+            PowerUp::Magnet => format!("(6) Magnet ({:.2}$)", price)
+        }
+    }
+
+    fn record_purchase(&mut self, power_up: PowerUp) {
+        self.purchase_counts.record(power_up);
+        self.last_purchase = Some((power_up, MainState::UNDO_WINDOW));
+        self.removed_words.clear();
+    }
+
+    // refunds the latest purchase while its undo window is open
+    fn undo_purchase(&mut self) {
+        self.recording.record_undo(self.stats.elapsed);
+        let power_up = match self.last_purchase.take() {
+            Some((power_up, _)) => power_up,
+            None => return
+        };
+
+        match power_up {
+            // the bought life was already lost, there is nothing left to take back
+            PowerUp::ExtraLife if self.remaining_lifes <= 1 => return,
+            PowerUp::ExtraLife => self.remaining_lifes -= 1,
+            PowerUp::RemoveWords => {
+                // put back front to back, at their old place when it still exists
+                while let Some((index, word, reward)) = self.removed_words.pop() {
+                    self.score -= reward;
+                    self.stats.forget_removed(word.label());
+                    if let Some(passage) = &mut self.passage {
+                        passage.unfinish_word();
+                    }
+
+                    let index = index.min(self.words.len());
+                    self.words.insert(index, word);
+                }
+            },
+            PowerUp::SlowSpawn => {
+                self.game_speed_up = self.speed_up_before_purchase;
+                self.slow_spawn_time = 0.0;
+                self.slow_spawn_taken = 0.0;
+            },
+            // the shield already took its hit
+            PowerUp::Shield if !self.shield_active => return,
+            PowerUp::Shield => self.shield_active = false,
+            // the pulled word is already on its way again
+            PowerUp::Magnet => return
+        }
+
+        self.purchase_counts.undo(power_up);
+        self.add_cash(power_up.price(&self.economy, self.purchases(power_up)));
+        self.toast_label = Some(String::from("Purchase undone"));
+        self.toast_time = MainState::TOAST_DURATION;
+    }
+
+    // the words are matched in update, an input that finishes one is left for the next step to take
+    // a miss is recorded as a keystroke, so the replay clears the input at the same time
+    fn submit_input(&mut self) {
+        let finishes_word = entities::matching_word(&self.words, &self.current_input, self.passage.is_some()).is_some();
+        let miss = match difficulty::failed_submit(&self.settings, self.remaining_lifes, finishes_word) {
+            Some(miss) => miss,
+            None => return
+        };
+
+        self.record_keystroke(stats::SUBMIT);
+        if miss.penalty > 0.0 {
+            let taken = self.take_cash(miss.penalty);
+            let popup_pos = Point2 { x: MainState::MISS_POPUP_X, y: self.spawn_band.1 };
+            self.point_popups.push(PointPopup::new(-taken, popup_pos, graphics::Color::from_rgb(255, 80, 80)));
+        }
+
+        // the game over itself happens in the next update step
+        if miss.loses_life && !debug::is_active() {
+            difficulty::lose_life(&mut self.remaining_lifes);
+            if miss.ends_run {
+                self.killer_word = Some(self.current_input.clone());
+            }
+        }
+
+        self.flash_time = MainState::FLASH_DURATION;
+        self.current_input = String::new();
+        self.input_burst = false;
+    }
+
+    fn record_keystroke(&mut self, key: char) {
+        self.keystroke_times.push_back(self.stats.elapsed);
+        self.recording.record_key(self.stats.elapsed, key);
+
+        if self.settings.keystroke_log {
+            self.keystroke_log.push((self.stats.elapsed, key));
+        }
+    }
+
+    // swaps in the track picked in the settings, at the current volume
+    fn change_music(&mut self, ctx: &mut Context) {
+        let _ = self.assets.background_music.stop(ctx);
+
+        self.assets.background_music = Assets::load_music(ctx, &self.settings.music_track, self.assets.audio_enabled);
+
+        self.assets.background_music.set_volume(self.sound_volume);
+        let _ = self.assets.background_music.play(ctx);
+    }
+
+    fn toggle_fullscreen(&mut self, ctx: &mut Context) {
+        self.settings.fullscreen ^= true;
+        self.settings.save(ctx);
+
+        self.apply_fullscreen(ctx);
+    }
+
+    // desktop fullscreen keeps the monitor's resolution, the hud follows the new drawable size
+    fn apply_fullscreen(&mut self, ctx: &mut Context) {
+        let fullscreen_type = if self.settings.fullscreen { FullscreenType::Desktop } else { FullscreenType::Windowed };
+        if let Err(error) = graphics::set_fullscreen(ctx, fullscreen_type) {
+            eprintln!("Couldn't change the fullscreen mode: {}", error);
+            return;
+        }
+
+        let (width, height) = graphics::drawable_size(ctx);
+        self.apply_screen_size(ctx, width, height);
+    }
+
+    fn apply_screen_size(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        self.screen_width = width;
+        self.screen_height = height;
+        let _ = graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, width, height));
+
+        self.spawn_band = self.compute_spawn_band(ctx);
+    }
+
+    fn toggle_focus_mode(&mut self, ctx: &mut Context) {
+        self.settings.focus_mode ^= true;
+        self.settings.save(ctx);
+
+        self.info_panel = MainState::build_info_panel(&self.settings, &self.economy, self.assets.font);
+    }
+
+    fn toggle_speedrun_hud(&mut self, ctx: &mut Context) {
+        self.settings.speedrun_hud ^= true;
+        self.settings.save(ctx);
+
+        self.info_panel = MainState::build_info_panel(&self.settings, &self.economy, self.assets.font);
+    }
+
+    fn menu_item_label(&self, item: MenuItem) -> String {
+        match item {
+            MenuItem::ResumeSession => String::from("Resume last session"),
+            MenuItem::Play => String::from("Play"),
+            MenuItem::WatchReplay => String::from("Watch last run"),
+            MenuItem::ColorChangingWords => {
+                let state = if self.settings.color_changing_words { "on" } else { "off" };
+                format!("Color-changing words: {}", state)
+            },
+            MenuItem::SurvivalSummary => {
+                let state = if self.settings.survival_summary { "survival time" } else { "score" };
+                format!("Game over summary: {}", state)
+            },
+            MenuItem::Difficulty if self.settings.auto_difficulty => format!("Difficulty: Auto ({})", self.auto_tuning.difficulty),
+            MenuItem::Difficulty => format!("Difficulty: {}", self.settings.difficulty),
+            MenuItem::NextWordPreview => {
+                let state = if self.settings.next_word_preview { "on" } else { "off" };
+                format!("Next word preview: {}", state)
+            },
+            MenuItem::HeartLifes => {
+                let state = if self.settings.heart_lifes { "hearts" } else { "text" };
+                format!("Lifes as: {}", state)
+            },
+            MenuItem::ShowKps => {
+                let state = if self.settings.show_kps { "on" } else { "off" };
+                format!("Keystrokes per second: {}", state)
+            },
+            MenuItem::BreathingBackground => {
+                let state = if self.settings.breathing_background { "on" } else { "off" };
+                format!("Breathing background: {}", state)
+            },
+            MenuItem::Passage => {
+                let passage = if self.settings.passage.is_empty() {
+                    "off"
+                }
+                else {
+                    self.settings.passage.trim_start_matches(Passage::DIR).trim_start_matches('/').trim_end_matches(".txt")
+                };
+                format!("Passage: {}", passage)
+            },
+            MenuItem::Fullscreen => {
+                let state = if self.settings.fullscreen { "on" } else { "off" };
+                format!("Fullscreen: {}", state)
+            },
+            MenuItem::SpeedRamp => {
+                let state = if self.settings.speed_ramp { "on" } else { "off" };
+                format!("Speed ramp: {}", state)
+            },
+            MenuItem::ShrinkingWords => {
+                let state = if self.settings.shrinking_words { "on" } else { "off" };
+                format!("Shrinking words: {}", state)
+            },
+            MenuItem::PracticeWeakWords => {
+                let state = if self.settings.practice_weak_words { "on" } else { "off" };
+                format!("Practice weak words: {}", state)
+            },
+            MenuItem::AdaptiveSpawn => {
+                let state = if self.settings.adaptive_spawn { "on" } else { "off" };
+                format!("Adaptive spawn: {}", state)
+            },
+            MenuItem::DoubleSpeed => {
+                let state = if self.settings.double_speed { "on" } else { "off" };
+                format!("Double speed: {}", state)
+            },
+            MenuItem::MusicTrack => {
+                let track = self.settings.music_track.trim_start_matches('/').trim_end_matches("-music.wav");
+                format!("Music: {}", track)
+            },
+            MenuItem::StartingCash => format!("Starting cash: {:.2}$", self.settings.starting_cash),
+            MenuItem::Quit => String::from("Quit"),
+            MenuItem::Resume => String::from("Resume"),
+            MenuItem::Restart => String::from("Restart"),
+            MenuItem::QuitToMenu => String::from("Quit to Menu")
+        }
+    }
+
+    fn current_menu(&self) -> &'static [MenuItem] {
+        if self.phase == Phase::Paused {
+            &PAUSE_MENU
+        }
+        else if self.saved_session.is_none() {
+            &MAIN_MENU[1 ..]
+        }
+        else {
+            &MAIN_MENU
+        }
+    }
+
+    fn menu_key_down(&mut self, ctx: &mut Context, keycode: event::KeyCode) {
+        let items = self.current_menu();
+
+        match keycode {
+            event::KeyCode::Escape if self.phase == Phase::Paused => self.phase = Phase::Playing,
+            event::KeyCode::Escape => self.quit(ctx),
+            event::KeyCode::Up => {
+                self.selected_index = menu::select_previous(self.selected_index, items.len());
+                self.assets.menu_tick_sound.set_volume(self.sound_volume);
+                let _ = self.assets.menu_tick_sound.play(ctx);
+            },
+            event::KeyCode::Down => {
+                self.selected_index = menu::select_next(self.selected_index, items.len());
+                self.assets.menu_tick_sound.set_volume(self.sound_volume);
+                let _ = self.assets.menu_tick_sound.play(ctx);
+            },
+            event::KeyCode::Return |
+            event::KeyCode::NumpadEnter => {
+                self.assets.menu_confirm_sound.set_volume(self.sound_volume);
+                let _ = self.assets.menu_confirm_sound.play(ctx);
+
+                match items[self.selected_index] {
+                    MenuItem::ResumeSession => {
+                        if let Err(error) = self.resume_session(ctx) {
+                            eprintln!("Couldn't resume the last session: {}", error);
+                        }
+                    },
+                    MenuItem::Play => {
+                        // a new run takes the place of the interrupted one
+                        if self.saved_session.take().is_some() {
+                            filesystem_helper::discard_session(ctx);
+                            self.selected_index = 0;
+                        }
+                        self.reset(ctx);
+                        self.phase = Phase::Playing;
+                    },
+                    MenuItem::WatchReplay => self.watch_replay(ctx),
+                    MenuItem::Resume => self.phase = Phase::Playing,
+                    MenuItem::ColorChangingWords => self.toggle_color_changing_words(ctx),
+                    MenuItem::SurvivalSummary => {
+                        self.settings.survival_summary ^= true;
+                        self.settings.save(ctx);
+                    },
+                    // the auto difficulty comes after the hard one
+                    MenuItem::Difficulty => {
+                        if self.settings.auto_difficulty {
+                            self.settings.auto_difficulty = false;
+                            self.settings.difficulty = Difficulty::Easy;
+                        }
+                        else if self.settings.difficulty == Difficulty::Hard {
+                            self.settings.auto_difficulty = true;
+                        }
+                        else {
+                            self.settings.difficulty = self.settings.difficulty.next();
+                        }
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::NextWordPreview => {
+                        self.settings.next_word_preview ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::HeartLifes => {
+                        self.settings.heart_lifes ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::ShowKps => {
+                        self.settings.show_kps ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::BreathingBackground => {
+                        self.settings.breathing_background ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::Passage => {
+                        // the empty option turns the passage off
+                        let mut options = vec![String::new()];
+                        options.extend(self.passages.iter().cloned());
+                        let current = options.iter().position(|passage| *passage == self.settings.passage).unwrap_or(0);
+                        self.settings.passage = options[menu::select_next(current, options.len())].clone();
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::AdaptiveSpawn => {
+                        self.settings.adaptive_spawn ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::DoubleSpeed => {
+                        self.settings.double_speed ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::Fullscreen => self.toggle_fullscreen(ctx),
+                    MenuItem::SpeedRamp => {
+                        self.settings.speed_ramp ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::ShrinkingWords => {
+                        self.settings.shrinking_words ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::PracticeWeakWords => {
+                        self.settings.practice_weak_words ^= true;
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::StartingCash => {
+                        let presets = self.economy.starting_cash_presets();
+                        let current = presets.iter().position(|cash| *cash == self.settings.starting_cash).unwrap_or(0);
+                        self.settings.starting_cash = presets[menu::select_next(current, presets.len())];
+                        self.settings.save(ctx);
+                    },
+                    MenuItem::MusicTrack if !self.assets.music_tracks.is_empty() => {
+                        let tracks = &self.assets.music_tracks;
+                        let current = tracks.iter().position(|track| *track == self.settings.music_track).unwrap_or(0);
+                        self.settings.music_track = tracks[menu::select_next(current, tracks.len())].clone();
+                        self.settings.save(ctx);
+                        self.change_music(ctx);
+                    },
+                    // no tracks were found in the resources
+                    MenuItem::MusicTrack => (),
+                    MenuItem::Quit => self.quit(ctx),
+                    MenuItem::Restart => {
+                        self.reset(ctx);
+                        self.phase = Phase::Playing;
+                    },
+                    MenuItem::QuitToMenu => {
+                        self.reset(ctx);
+                        self.selected_index = 0;
+                        self.phase = Phase::Menu;
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+
+    fn draw_menu(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let title = if self.phase == Phase::Paused { "Paused" } else { "Type Racer" };
+        let mut title_panel = TextSprite::new(title, self.assets.font, MainState::MENU_TITLE_TEXT_SIZE);
+        let title_height = title_panel.height(ctx) + 2.0 * MainState::MENU_ITEM_SPACING;
+        let item_height = TextSprite::new("Menu", self.assets.font, MainState::MENU_ITEM_TEXT_SIZE).height(ctx) + MainState::MENU_ITEM_SPACING;
+
+        // small windows scroll through the items instead of cutting them off
+        let max_visible = ((self.screen_height - title_height) / item_height).floor().max(1.0) as usize;
+        let (first, last) = menu::visible_range(self.selected_index, self.current_menu().len(), max_visible);
+        let mut item_panels = self.current_menu()[first .. last].iter()
+            .map(|item| TextSprite::new(&self.menu_item_label(*item), self.assets.font, MainState::MENU_ITEM_TEXT_SIZE))
+            .collect::<Vec<TextSprite>>();
+
+        // the title and the items are centered as one block
+        let menu_height = title_height + item_panels.len() as f32 * item_height;
+
+        let mut top = ((self.screen_height - menu_height) / 2.0).max(0.0);
+        let title_pos = Point2 {
+            x: (self.screen_width - title_panel.width(ctx)) / 2.0,
+            y: top
         };
+        title_panel.draw(title_pos, graphics::Color::WHITE, ctx)?;
+        top += title_panel.height(ctx) + 2.0 * MainState::MENU_ITEM_SPACING;
+
+        let highlight_color = graphics::Color::from_rgb(192, 192, 192);
+        let selected_text_color = graphics::Color::from_rgb(48, 116, 115);
+
+        for (index, item_panel) in (first ..).zip(item_panels.iter_mut()) {
+            let item_pos = Point2 {
+                x: (self.screen_width - item_panel.width(ctx)) / 2.0,
+                y: top
+            };
+
+            if index == self.selected_index {
+                draw_helper::draw_text_background(item_pos, item_panel.width(ctx), item_panel.height(ctx), 10.0, highlight_color, ctx);
+                item_panel.draw(item_pos, selected_text_color, ctx)?;
+            }
+            else {
+                item_panel.draw(item_pos, graphics::Color::WHITE, ctx)?;
+            }
+
+            top += item_panel.height(ctx) + MainState::MENU_ITEM_SPACING;
+        }
+
+        Ok(())
+    }
+
+    // the regular hud, the labels along the top and the bottom edges and the power ups
+    fn draw_hud(&mut self, ctx: &mut Context, shake_translation: Point2<f32>) -> GameResult<()> {
+        let label_margin = 10.0;
+        let game_status_panel_color = graphics::Color::WHITE;
+
+        // Draw current volume
+        let mut top_left = Point2 {
+            x: 0.0,
+            y: 0.0
+        };
+
+        draw_helper::translate(&mut top_left, &shake_translation);
+
+        let options_label = format!("({}) for Info|", self.settings.info_key);
+        let mut options_panel = TextSprite::new(&options_label, self.assets.font, MainState::TOP_PANEL_TEXT_SIZE);
+        top_left.x += label_margin;
+        options_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
+        top_left.x += options_panel.width(ctx);
+
+        let current_volume_label = format!("Volume: {:.0}%", self.sound_volume * 100.0);
+        let mut current_volume_panel = TextSprite::new(&current_volume_label, self.assets.font, MainState::TOP_PANEL_TEXT_SIZE);
+        top_left.x += label_margin;
+        current_volume_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
+        top_left.x += current_volume_panel.width(ctx);
+
+        // Draw keystrokes per second
+        if self.settings.show_kps {
+            let kps = stats::keystrokes_in_window(&self.keystroke_times, self.stats.elapsed, MainState::KPS_WINDOW);
+            let kps_label = format!("KPS: {}", kps);
+            let mut kps_panel = TextSprite::new(&kps_label, self.assets.font, MainState::TOP_PANEL_TEXT_SIZE);
+            top_left.x += label_margin;
+            kps_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
+            top_left.x += kps_panel.width(ctx);
+        }
+
+        // Draw passage progress
+        if let Some(passage) = &self.passage {
+            let (finished, total) = passage.progress();
+            let passage_label = format!("Passage: {}/{}", finished, total);
+            let mut passage_panel = TextSprite::new(&passage_label, self.assets.font, MainState::TOP_PANEL_TEXT_SIZE);
+            top_left.x += label_margin;
+            passage_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
+            top_left.x += passage_panel.width(ctx);
+        }
+
+        // Draw the difficulty and the mode, left out when the window is too narrow for it next to the power ups
+        let mode_label = format!("{} · {}", self.settings.difficulty, self.mode_name());
+        let mut mode_panel = TextSprite::new(&mode_label, self.assets.font, MainState::TOP_PANEL_TEXT_SIZE);
+        let power_ups_left = self.power_up_rects.iter().flatten().map(|rect| rect.x).fold(self.screen_width, f32::min);
+        if top_left.x + 2.0 * label_margin + mode_panel.width(ctx) <= power_ups_left {
+            top_left.x += label_margin;
+            mode_panel.draw(top_left, graphics::Color::from_rgb(160, 160, 160), ctx).unwrap();
+        }
+
+        // Draw current cash
+        let mut bottom_right = Point2 {
+            x: self.screen_width,
+            y: self.screen_height
+        };
+
+        draw_helper::translate(&mut bottom_right, &shake_translation);
+
+        let cash_label = if self.settings.show_cash_rate {
+            format!("Cash: {:.2} ({:.1}/min)", self.wallet.cash(), self.cash_per_minute())
+        }
+        else {
+            format!("Cash: {:.2}", self.wallet.cash())
+        };
+        let mut cash_panel = TextSprite::new(&cash_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+        bottom_right.x -= cash_panel.width(ctx) + label_margin;
+        bottom_right.y -= cash_panel.height(ctx);
+        cash_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
+
+        // Draw the latest cash change, above the cash
+        if let Some(delta_label) = self.wallet.delta().label().filter(|_| self.settings.show_cash_delta) {
+            let mut delta_panel = TextSprite::new(&delta_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            let mut delta_color = if self.wallet.delta().amount() > 0.0 { graphics::Color::from_rgb(140, 255, 140) } else { graphics::Color::from_rgb(255, 80, 80) };
+            delta_color.a = self.wallet.delta().alpha();
+            let delta_pos = Point2 { x: bottom_right.x, y: bottom_right.y - delta_panel.height(ctx) };
+            delta_panel.draw(delta_pos, delta_color, ctx).unwrap();
+        }
+        bottom_right.y += cash_panel.height(ctx);
+
+        // Draw remaining lifes
+        match &self.assets.heart_image {
+            Some(heart_image) if self.settings.heart_lifes => {
+                let heart_width = heart_image.width() as f32;
+                let hearts_width = self.remaining_lifes as f32 * (heart_width + MainState::HEART_SPACING) - MainState::HEART_SPACING;
+                bottom_right.x -= hearts_width.max(0.0) + label_margin;
+
+                // centered on the text row, next to the cash label
+                let mut heart_pos = Point2 {
+                    x: bottom_right.x,
+                    y: bottom_right.y - (cash_panel.height(ctx) + heart_image.height() as f32) / 2.0
+                };
+
+                for _ in 0 .. self.remaining_lifes {
+                    graphics::draw(ctx, heart_image, graphics::DrawParam::default().dest(heart_pos))?;
+                    heart_pos.x += heart_width + MainState::HEART_SPACING;
+                }
+            },
+            _ => {
+                let lifes_label = format!("Lifes: {}", self.remaining_lifes);
+                let mut lifes_panel = TextSprite::new(&lifes_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+                bottom_right.x -= lifes_panel.width(ctx) + label_margin;
+                bottom_right.y -= lifes_panel.height(ctx);
+                lifes_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
+                bottom_right.y += lifes_panel.height(ctx);
+            }
+        }
+
+        // Draw the strict submit badge
+        if self.settings.strict_submit {
+            let mut strict_panel = TextSprite::new("STRICT", self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= strict_panel.width(ctx) + label_margin;
+            bottom_right.y -= strict_panel.height(ctx);
+            strict_panel.draw(bottom_right, graphics::Color::from_rgb(255, 80, 80), ctx).unwrap();
+            bottom_right.y += strict_panel.height(ctx);
+        }
+
+        // Draw the warmup badge
+        if self.warmup_time > 0.0 {
+            let mut warmup_panel = TextSprite::new("WARMUP", self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= warmup_panel.width(ctx) + label_margin;
+            bottom_right.y -= warmup_panel.height(ctx);
+            warmup_panel.draw(bottom_right, graphics::Color::from_rgb(120, 255, 160), ctx).unwrap();
+            bottom_right.y += warmup_panel.height(ctx);
+        }
+
+        // Draw the time left of a timed slow spawn
+        if self.slow_spawn_time > 0.0 {
+            let slow_spawn_label = format!("Slow spawn {:.0}s", self.slow_spawn_time.ceil());
+            let mut slow_spawn_panel = TextSprite::new(&slow_spawn_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= slow_spawn_panel.width(ctx) + label_margin;
+            bottom_right.y -= slow_spawn_panel.height(ctx);
+            slow_spawn_panel.draw(bottom_right, graphics::Color::from_rgb(120, 180, 255), ctx).unwrap();
+            bottom_right.y += slow_spawn_panel.height(ctx);
+        }
+
+        // Draw the forgiven escapes bought with the panic button
+        if self.invulnerable_time > 0.0 {
+            let invulnerable_label = format!("SAFE {:.0}s", self.invulnerable_time.ceil());
+            let mut invulnerable_panel = TextSprite::new(&invulnerable_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= invulnerable_panel.width(ctx) + label_margin;
+            bottom_right.y -= invulnerable_panel.height(ctx);
+            invulnerable_panel.draw(bottom_right, graphics::Color::from_rgb(120, 255, 160), ctx).unwrap();
+            bottom_right.y += invulnerable_panel.height(ctx);
+        }
+
+        // Draw the panic button once there's enough cash for it
+        if self.panic_available() {
+            let panic_label = format!("(5) Panic {:.2}$", self.wallet.cash());
+            let mut panic_panel = TextSprite::new(&panic_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= panic_panel.width(ctx) + label_margin;
+            bottom_right.y -= panic_panel.height(ctx);
+            panic_panel.draw(bottom_right, graphics::Color::from_rgb(255, 150, 80), ctx).unwrap();
+            bottom_right.y += panic_panel.height(ctx);
+        }
+
+        // Draw the shield
+        if self.shield_active {
+            let mut shield_panel = TextSprite::new("Shield", self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_right.x -= shield_panel.width(ctx) + label_margin;
+            bottom_right.y -= shield_panel.height(ctx);
+            shield_panel.draw(bottom_right, graphics::Color::from_rgb(120, 180, 255), ctx).unwrap();
+            bottom_right.y += shield_panel.height(ctx);
+        }
+
+        // Draw current score
+        let score_label = format!("Score: {:.2}", self.score);
+        let mut score_panel = TextSprite::new(&score_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+        bottom_right.x -= score_panel.width(ctx) + label_margin;
+        bottom_right.y -= score_panel.height(ctx);
+        score_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
+
+        // Draw power ups, the ones the player can't afford yet are greyed out
+        let power_up_color = graphics::Color::WHITE;
+        let unaffordable_color = graphics::Color::new(0.5, 0.5, 0.5, 0.6);
+        let mut top_right = Point2 {
+            x: self.screen_width,
+            y: 0.0
+        };
+
+        draw_helper::translate(&mut top_right, &shake_translation);
+
+        for (index, power_up) in MainState::POWER_UP_ORDER.iter().enumerate() {
+            self.power_up_rects[index] = None;
+            // the slow spawn buff is useless without the speed ramp
+            let is_available = match power_up {
+                PowerUp::SlowSpawn => self.settings.speed_ramp && self.slow_spawn_time <= 0.0,
+                PowerUp::Shield => !self.shield_active,
+                PowerUp::Magnet => self.can_pull_back(),
+                _ => true
+            };
+            if !is_available {
+                continue;
+            }
+
+            let price = power_up.price(&self.economy, self.purchases(*power_up));
+            let panel = &mut self.power_up_panels[index];
+            if self.settings.escalating_prices {
+                panel.set_text(&MainState::power_up_label(*power_up, price));
+            }
+
+            let color = if self.wallet.cash() >= price { power_up_color } else { unaffordable_color };
+            top_right.x -= panel.width(ctx) + label_margin;
+            panel.draw(top_right, color, ctx).unwrap();
+            self.power_up_rects[index] = Some(graphics::Rect::new(top_right.x, top_right.y, panel.width(ctx), panel.height(ctx)));
+        }
+
+        // a thin bar under the power ups fills up towards the next one
+        let power_ups_bottom = self.power_up_rects.iter().flatten().map(|rect| rect.bottom()).fold(0.0, f32::max);
+        if let Some(progress) = self.economy.next_buff_progress(self.wallet.cash()) {
+            let bar_width = (self.screen_width - label_margin - top_right.x) * progress;
+            let bar = graphics::Rect::new(top_right.x, power_ups_bottom, bar_width, MainState::BUFF_PROGRESS_BAR_HEIGHT);
+            if bar.w > 0.0 {
+                let bar_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), bar, unaffordable_color)?;
+                graphics::draw(ctx, &bar_mesh, graphics::DrawParam::default())?;
+            }
+        }
+
+        Ok(())
+    }
 
-        Ok(start_state)
+    // one tidy block in the top left corner for streaming, the power ups are only bought with their keys then
+    fn draw_speedrun_hud(&mut self, ctx: &mut Context, shake_translation: Point2<f32>) -> GameResult<()> {
+        const MARGIN: f32 = 10.0;
+
+        self.power_up_rects = [None; 5];
+
+        let summary = self.stats.speedrun_summary(self.remaining_lifes);
+        let mut summary_panel = TextSprite::new(&summary, self.assets.font, MainState::SPEEDRUN_TEXT_SIZE);
+        let mut summary_pos = Point2 { x: 2.0 * MARGIN, y: 2.0 * MARGIN };
+        draw_helper::translate(&mut summary_pos, &shake_translation);
+
+        let background = graphics::Rect::new(summary_pos.x - MARGIN, summary_pos.y - MARGIN, summary_panel.width(ctx) + 2.0 * MARGIN, summary_panel.height(ctx) + 2.0 * MARGIN);
+        let background_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), background, graphics::Color::new(0.0, 0.0, 0.0, 0.6))?;
+        graphics::draw(ctx, &background_mesh, graphics::DrawParam::default())?;
+
+        summary_panel.draw(summary_pos, graphics::Color::WHITE, ctx)
     }
 }
 
 impl event::EventHandler for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        if self.game_over {
-            return Ok(())
+        const FPS_CAP: u32 = 60;
+
+        if debug::is_active() && self.frame_times.record(timer::delta(ctx).as_secs_f32()) {
+            if let Some((min, max, avg)) = self.frame_times.summary() {
+                eprintln!("Frame times over {} frames: min {:.2}ms, max {:.2}ms, avg {:.2}ms",
+                          MainState::FRAME_TIMES_WINDOW, min * 1000.0, max * 1000.0, avg * 1000.0);
+            }
         }
 
-        const FPS_CAP: u32 = 60;
+        // the caret blinks on the game over screen as well
+        self.cursor_timer = (self.cursor_timer + timer::delta(ctx).as_secs_f32()) % (2.0 * MainState::CURSOR_BLINK);
+
+        // reading the info panel pauses the run as well
+        if self.phase != Phase::Playing || self.show_info {
+            // drop the frame time gathered outside of a run, so resuming doesn't fast-forward the words
+            while timer::check_update_time(ctx, FPS_CAP) {
+                if self.music_fade_time > 0.0 {
+                    self.music_fade_time = (self.music_fade_time - 1.0 / (FPS_CAP as f32)).max(0.0);
+
+                    let faded = 1.0 - self.music_fade_time / MainState::MUSIC_FADE_DURATION;
+                    let volume_share = 1.0 - faded * (1.0 - MainState::GAME_OVER_MUSIC_VOLUME);
+                    self.assets.background_music.set_volume(self.sound_volume * volume_share);
+                }
+            }
+
+            return Ok(())
+        }
 
         while timer::check_update_time(ctx, FPS_CAP)
         {
+            // the run clock stays at zero until the player is there to type
+            if !self.started {
+                continue;
+            }
+
             let seconds = 1.0 / (FPS_CAP as f32);
+            // the spawns of this step are recorded at its start, along with the keys typed before it
+            let step_start = self.stats.elapsed;
+
+            if let Some(playback) = &mut self.playback {
+                for event in playback.due(step_start) {
+                    match event {
+                        ReplayEvent::Spawn { label, y, speed, is_color_changing, is_shrinking, .. } => self.spawn_word(ctx, &label, y, speed, is_color_changing, is_shrinking)?,
+                        ReplayEvent::Key { key, .. } if key == stats::BACKSPACE => {
+                            self.current_input.pop();
+                            self.record_keystroke(key);
+                        },
+                        // records the keystroke itself when it's a miss again
+                        ReplayEvent::Key { key, .. } if key == stats::SUBMIT => self.submit_input(),
+                        ReplayEvent::PowerUp { power_up, .. } => self.use_power_up(ctx, power_up),
+                        ReplayEvent::Undo { .. } => self.undo_purchase(),
+                        ReplayEvent::Panic { .. } => self.press_panic_button(),
+                        ReplayEvent::Key { key, .. } => {
+                            self.current_input.push(key);
+                            self.record_keystroke(key);
+                        }
+                    }
+                }
+            }
+
+            self.stats.advance(seconds);
 
             // Screen shaker
             self.time_until_shake -= seconds;
@@ -174,221 +1769,435 @@ impl event::EventHandler for MainState {
                 }
             }
 
-            // Spawn words
+            // Sudden death, turns off again when the player buys back lifes
+            let sudden_death = self.remaining_lifes == 1;
+            if sudden_death != self.sudden_death {
+                self.sudden_death = sudden_death;
+
+                let pitch = if sudden_death { MainState::SUDDEN_DEATH_MUSIC_PITCH } else { 1.0 };
+                self.set_music_pitch(ctx, pitch);
+            }
+
+            self.time_since_typed_word += seconds;
+            self.warmup_time = (self.warmup_time - seconds).max(0.0);
+            self.invulnerable_time = (self.invulnerable_time - seconds).max(0.0);
+
+            if self.slow_spawn_time > 0.0 {
+                self.slow_spawn_time -= seconds;
+                if self.slow_spawn_time <= 0.0 {
+                    self.slow_spawn_time = 0.0;
+                    self.game_speed_up = power_up::restored_speed_up(self.game_speed_up, self.slow_spawn_taken);
+                    self.slow_spawn_taken = 0.0;
+                }
+            }
+
+            // Purchase undo window
+            if let Some((_, time_left)) = &mut self.last_purchase {
+                *time_left -= seconds;
+                if *time_left <= 0.0 {
+                    self.last_purchase = None;
+                    self.removed_words.clear();
+                }
+            }
+
+            // Toast and flash
+            if self.toast_label.is_some() {
+                self.toast_time -= seconds;
+                if self.toast_time <= 0.0 {
+                    self.toast_label = None;
+                }
+            }
+
+            self.flash_time = (self.flash_time - seconds).max(0.0);
+            if let Some((_, time_left)) = &mut self.magnet_trail {
+                *time_left -= seconds;
+            }
+            self.magnet_trail = self.magnet_trail.filter(|(_, time_left)| *time_left > 0.0);
+            self.wallet.update(seconds);
+
+            for popup in &mut self.point_popups {
+                popup.update(seconds);
+            }
+            self.point_popups.retain(|popup| !popup.is_expired());
+            self.breath_time = (self.breath_time + seconds) % MainState::BREATH_PERIOD;
+
+            // Keep only the keystrokes inside the kps window
+            while let Some(time) = self.keystroke_times.front() {
+                if self.stats.elapsed - time <= MainState::KPS_WINDOW {
+                    break;
+                }
+
+                self.keystroke_times.pop_front();
+            }
+
+            // Spawn words, a held spawn happens as soon as the board is clear
+            let wave_waiting = self.advance_wave(seconds);
             self.time_until_next_word -= seconds;
-            if self.time_until_next_word <= 0.0 {
-                let margin = 10.0;
-                let top_height = MainState::TOP_PANEL_TEXT_SIZE + margin;
-                let bot_height = self.screen_height - MainState::BOT_PANEL_TEXT_SIZE - margin;
-                let random_point = Point2 {
-                    x: 0.0,
-                    y: self.rng.gen_range(top_height .. bot_height)
-                };
-            
-                let random_word = self.words_pool[self.rng.gen_range(0 .. self.words_pool.len())].clone();
-                
-                let random_speed = self.rng.gen_range(100.0 .. 300.0);
-                let percentage: u8 = self.rng.gen_range(0 ..= 100);
-                let is_color_changing = percentage < 30;
-                let word_sprite = Box::new(TextSprite::new(&random_word, ctx, 32.0)?);
-                let word = Word::new(&random_word, random_point, random_speed, word_sprite, is_color_changing)?;
-    
-                self.words.push(word);
-                let min_word_gen_time = 3.0 - self.game_speed_up;
-                let max_word_gen_time = 3.5 - self.game_speed_up;
-                self.time_until_next_word = self.rng.gen_range(min_word_gen_time .. max_word_gen_time);
-                self.game_speed_up += 0.03;
-            }
-
-            for word in self.words.iter_mut() {
-                word.update(seconds);
-    
-                if word.label() == self.current_input {
+            let spawn_held = difficulty::holds_spawn(self.settings.last_stand, self.remaining_lifes, self.words.len());
+            // a full board skips the spawn and waits a whole gap again, without speeding up
+            if self.playback.is_none() && self.time_until_next_word <= 0.0 && difficulty::board_full(self.words.len(), self.settings.max_visible_words) {
+                self.time_until_next_word = self.next_spawn_gap();
+            }
+            else if self.playback.is_none() && !spawn_held && !wave_waiting && self.time_until_next_word <= 0.0 {
+                // the word was already chosen when the timer started, so it could be previewed
+                let next_word = self.next_word.take().or_else(|| self.pick_word(None));
+
+                // nothing is left to spawn once a passage ran out of words
+                if let Some(random_word) = &next_word {
+                    let word_sprite = self.word_sprite(ctx, random_word);
+                    let word_size = Vector2 { x: word_sprite.width(ctx), y: word_sprite.height(ctx) };
+                    let occupied = self.words.iter().map(|word| word.bounding_rect(ctx)).collect::<Vec<graphics::Rect>>();
+                    // the lanes are spaced by the regular word height, the shrunk fonts of the long words fit in them too
+                    let lanes = if self.settings.spawn_lanes > 0 {
+                        let lane_height = TextSprite::new("word", self.assets.font, MainState::WORD_TEXT_SIZE).height(ctx);
+                        draw_helper::lane_tops(self.spawn_band, lane_height, self.settings.spawn_lanes)
+                    }
+                    else {
+                        Vec::new()
+                    };
+                    let spawn_y = if lanes.is_empty() {
+                        draw_helper::find_spawn_point(&mut self.rng, self.spawn_band, word_size, &occupied, self.settings.spawn_retries).map(|point| point.y)
+                    }
+                    else {
+                        draw_helper::pick_lane(&mut self.rng, &lanes, word_size, &occupied)
+                    };
+
+                    // a crowded board lets the word overlap rather than skipping it
+                    let random_y = match spawn_y {
+                        Some(y) => y,
+                        None if !lanes.is_empty() => lanes[self.rng.gen_range(0 .. lanes.len())],
+                        None => {
+                            if debug::is_active() {
+                                eprintln!("No free spawn position for {} after {} tries, overlapping", random_word, self.settings.spawn_retries);
+                            }
+
+                            let (min_y, max_y) = self.spawn_band;
+                            self.rng.gen_range(min_y .. max_y)
+                        }
+                    };
+
+                    let mut random_speed = self.settings.difficulty.random_word_speed(&mut self.rng);
+                    if let Some(cap) = self.settings.difficulty.grace_speed_cap(self.spawned_words).filter(|_| self.settings.first_words_grace) {
+                        random_speed = random_speed.min(cap);
+                    }
+                    random_speed *= difficulty::speed_factor(self.settings.double_speed);
+                    self.spawned_words += 1;
+                    // the roll is skipped entirely when the player disabled color-changing words
+                    let is_color_changing = self.settings.color_changing_words &&
+                        difficulty::roll_chance(&mut self.rng, self.settings.difficulty.color_changing_chance());
+                    let is_shrinking = self.settings.shrinking_words && difficulty::roll_chance(&mut self.rng, MainState::SHRINKING_WORD_CHANCE);
+                    self.spawn_word(ctx, random_word, random_y, random_speed, is_color_changing, is_shrinking)?;
+                    self.recording.record_spawn(step_start, random_word, random_y, random_speed, is_color_changing, is_shrinking);
+                    self.wave_spawns_left = self.wave_spawns_left.saturating_sub(1);
+                }
+
+                self.time_until_next_word = self.next_spawn_gap();
+                self.next_word = self.pick_word(next_word.as_deref());
+                self.game_speed_up = difficulty::next_speed_up(self.game_speed_up, self.settings.speed_ramp);
+            }
+
+            if stats::is_input_burst(self.chars_this_frame, self.settings.max_chars_per_frame) {
+                self.input_burst = true;
+            }
+            self.chars_this_frame = 0;
+
+            let escape_line = self.escape_line();
+            // passage words spawn in order and only the oldest one on screen can be typed
+            let matched = entities::matching_word(&self.words, &self.current_input, self.passage.is_some());
+            // paid out after the loop, the words are borrowed in it
+            let mut earned_cash = 0.0;
+            for (index, word) in self.words.iter_mut().enumerate() {
+                let width = word.bounding_rect(ctx).w;
+                let escaped = word.update(seconds, escape_line, width);
+                let in_turn = self.passage.is_none() || index == 0;
+                word.is_targeted = in_turn && word.is_prefixed_by(&self.current_input);
+
+                if matched == Some(index) {
+                    if self.input_burst && self.ranked {
+                        self.ranked = false;
+                        eprintln!("Pasted input finished the word {}, the run won't be ranked", word.label());
+                    }
+
                     word.is_typed = true;
-                    
-                    self.score += word.get_reward();
-                    self.cash += word.get_reward();
+                    self.stats.record_typed(word.label());
+                    stats::push_typed_log(&mut self.typed_log, word.label(), MainState::TYPED_LOG_LEN);
+                    if let Some(passage) = &mut self.passage {
+                        passage.finish_word();
+                    }
+
+                    let reward = word.get_reward(&self.economy);
+                    self.score += reward;
+                    earned_cash += self.economy.cash_for(reward);
+                    self.stats.record_cash(self.economy.cash_for(reward));
+
+                    // the bonus is paid before the word joins the combo
+                    let combo_bonus = self.economy.combo_bonus(self.time_since_typed_word, self.combo);
+                    earned_cash += combo_bonus;
+                    self.stats.record_cash(combo_bonus);
+
+                    let popup_color = if combo_bonus > 0.0 {
+                        graphics::Color::from_rgb(255, 165, 0)
+                    }
+                    else if word.is_color_changing {
+                        Word::TYPED_COLOR
+                    }
+                    else {
+                        graphics::Color::from_rgb(140, 255, 140)
+                    };
+                    self.point_popups.push(PointPopup::new(reward, word.pos, popup_color));
+                    self.combo = if self.time_since_typed_word <= self.economy.combo_window { self.combo + 1 } else { 1 };
+                    self.time_since_typed_word = 0.0;
 
                     self.assets.word_typed_sound.set_volume(self.sound_volume);
+                    self.assets.word_typed_sound.set_pitch(stats::streak_pitch(self.stats.streak));
                     let _ = self.assets.word_typed_sound.play(ctx);
 
+                    if stats::is_milestone(self.stats.typed_words, MainState::MILESTONE_INTERVAL) {
+                        self.toast_label = Some(format!("{} words!", self.stats.typed_words));
+                        self.toast_time = MainState::TOAST_DURATION;
+                        self.flash_time = MainState::FLASH_DURATION;
+
+                        self.assets.milestone_sound.set_volume(self.sound_volume);
+                        let _ = self.assets.milestone_sound.play(ctx);
+                    }
+
                     // clear the input field after successfully typed word
                     self.current_input = String::new();
+                    self.input_burst = false;
                 }
 
-                if word.pos.x >= self.screen_width {
-                    word.is_typed = true;
-
-                    if !debug::is_active() {
-                        // don't end the game when debug is active
-                        self.remaining_lifes -= 1;
+                else if escaped {
+                    self.wave_escaped = true;
+                    if let Some(passage) = &mut self.passage {
+                        passage.finish_word();
+                    }
 
-                        if self.remaining_lifes == 0 {
-                            self.game_over = true;
+                    match power_up::escape_cost(self.shield_active, self.warmup_time.max(self.invulnerable_time)) {
+                        EscapeCost::Free => self.stats.record_escaped(word.label()),
+                        EscapeCost::Shield => {
+                            self.shield_active = false;
+                            self.stats.record_shielded(word.label());
+                        },
+                        EscapeCost::Life => {
+                            self.stats.record_escaped(word.label());
+
+                            if self.settings.clear_input_on_escape {
+                                self.current_input = String::new();
+                                self.input_burst = false;
+                            }
+
+                            // don't end the game when debug is active
+                            if !debug::is_active() && difficulty::lose_life(&mut self.remaining_lifes) {
+                                self.killer_word = Some(String::from(word.label()));
+                                self.phase = Phase::GameOver;
+                            }
                         }
                     }
                 }
             }
+            self.add_cash(earned_cash);
 
-            self.words.retain(|word| !word.is_typed);
-        }
+            self.words.retain(|word| !word.is_expired());
 
-        Ok(())
-    }
+            // a strict submit can take the last life in between two steps
+            if self.passage.as_ref().is_some_and(|passage| passage.is_complete()) || self.remaining_lifes == 0 {
+                self.phase = Phase::GameOver;
+            }
 
-    fn key_down_event(&mut self, ctx: &mut Context, keycode: event::KeyCode, _keymods: event::KeyMods, _repeat: bool) {
-        
-        match keycode {
-            event::KeyCode::Escape => event::quit(ctx),
-            event::KeyCode::Key1 |
-            event::KeyCode::Numpad1 => {
-                if self.cash >= MainState::BUY_LIFE_TAX {
-                    self.cash -= MainState::BUY_LIFE_TAX;
-                    self.remaining_lifes += 1;
-                }
-            },
-            event::KeyCode::Key2 |
-            event::KeyCode::Numpad2 => {
-                if self.cash >= MainState::REMOVE_WORDS_TAX && self.words.len() > 0 {
-                    self.cash -= MainState::REMOVE_WORDS_TAX;
-
-                    if self.words.len() <= MainState::REMOVE_WORDS_COUNT {
-                        self.words.iter_mut().for_each(|word| {
-                            word.is_typed = true;
-                            self.score += word.get_reward();
-                        });
+            // a replay doesn't overwrite the files of the run it shows
+            if self.phase == Phase::GameOver && self.playback.is_none() {
+                if !self.resumed {
+                    if let Err(error) = filesystem_helper::save_replay(ctx, &self.recording) {
+                        eprintln!("Couldn't save the replay: {}", error);
                     }
-                    else {
-                        let sample_indexes = seq::index::sample(&mut self.rng, self.words.len(), MainState::REMOVE_WORDS_COUNT);
+                }
 
-                        for index in sample_indexes.iter() {
-                            self.words[index].is_typed = true;
-                            self.score += self.words[index].get_reward();
-                        }
+                if self.stats.escaped_words > 0 {
+                    for label in self.stats.escaped_labels() {
+                        self.missed_words.record(label);
                     }
+                    self.missed_words.save(ctx);
                 }
-            },
-            event::KeyCode::Key3 |
-            event::KeyCode::Numpad3 => {
-                if self.cash >= MainState::SLOW_WORD_SPAWN_TAX {
-                    self.cash -= MainState::SLOW_WORD_SPAWN_TAX;
-                    self.game_speed_up /= 2.0;
-                }
-            },
-            event::KeyCode::NumpadAdd => {
-                if self.sound_volume + MainState::SOUND_VOLUME_STEP <= 100.0 {
-                    self.sound_volume += MainState::SOUND_VOLUME_STEP;
-                    self.assets.background_music.set_volume(self.sound_volume);
+
+                if let Err(error) = filesystem_helper::save_run_report(ctx, &self.stats.report(self.purchase_counts)) {
+                    eprintln!("Couldn't export the run report: {}", error);
                 }
-            },
-            event::KeyCode::NumpadSubtract => {
-                if self.sound_volume - MainState::SOUND_VOLUME_STEP >= 0.0 {
-                    self.sound_volume -= MainState::SOUND_VOLUME_STEP;
-                    self.assets.background_music.set_volume(self.sound_volume);
+
+                if self.settings.keystroke_log {
+                    let csv = stats::format_keystroke_csv(&self.keystroke_log);
+                    if let Err(error) = filesystem_helper::save_keystroke_log(ctx, &csv) {
+                        eprintln!("Couldn't export the keystroke log: {}", error);
+                    }
                 }
-            },
-            event::KeyCode::Grave => {
-                self.show_info ^= true;
             }
-            event::KeyCode::Minus => {
-                self.current_input += "-";
-            },
-            event::KeyCode::Return => {
-                if !self.saved_score {
-                    self.scoreboard = filesystem_helper::save_score(ctx, self.current_input.clone(), self.score, MainState::SCOREBOARD_SIZE);
-                    self.current_input = String::new();
-                    self.saved_score = true;
-                }
-            },
-            event::KeyCode::A => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "a", "A")
-            },
-            event::KeyCode::B => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "b", "B")
-            },
-            event::KeyCode::C => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "c", "C")
-            },
-            event::KeyCode::D => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "d", "D")
-            },
-            event::KeyCode::E => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "e", "E")
-            },
-            event::KeyCode::F => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "f", "F")
-            },
-            event::KeyCode::G => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "g", "G")
-            },
-            event::KeyCode::H => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "h", "H")
-            },
-            event::KeyCode::I => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "i", "I")
-            },
-            event::KeyCode::J => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "j", "J")
-            },
-            event::KeyCode::K => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "k", "K")
-            },
-            event::KeyCode::L => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "l", "L")
-            },
-            event::KeyCode::M => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "m", "M")
-            },
-            event::KeyCode::N => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "n", "N")
-            },
-            event::KeyCode::O => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "o", "O")
-            },
-            event::KeyCode::P => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "p", "P")
-            },
-            event::KeyCode::Q => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "q", "Q")
-            },
-            event::KeyCode::R => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "r", "R")
-            },
-            event::KeyCode::S => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "s", "S")
-            },
-            event::KeyCode::T => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "t", "T")
-            },
-            event::KeyCode::U => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "u", "U")
-            },
-            event::KeyCode::V => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "v", "V")
-            },
-            event::KeyCode::W => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "w", "W")
+
+            if self.phase == Phase::GameOver {
+                self.slow_down_music(ctx);
+
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: event::KeyCode, _keymods: event::KeyMods, repeat: bool) {
+        if keycode == event::KeyCode::Capital && !repeat {
+            self.caps_lock ^= true;
+        }
+
+        if self.phase == Phase::Menu || self.phase == Phase::Paused {
+            self.menu_key_down(ctx, keycode);
+            return;
+        }
+
+        if self.show_info && self.phase == Phase::Playing {
+            if keycode == event::KeyCode::Escape {
+                self.show_info = false;
+            }
+
+            return;
+        }
+
+        // a replay only listens to the pause key, the rest comes from the recording
+        if self.playback.is_some() && self.phase == Phase::Playing && keycode != event::KeyCode::Escape {
+            return;
+        }
+
+        match keycode {
+            event::KeyCode::Escape if self.phase == Phase::Playing => {
+                self.selected_index = 0;
+                self.phase = Phase::Paused;
             },
-            event::KeyCode::X => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "x", "X")
+            event::KeyCode::Escape => self.quit(ctx),
+            // the cash and the buys are final once the run is over, the run report shows them
+            event::KeyCode::Key1 |
+            event::KeyCode::Numpad1 if self.phase == Phase::Playing => self.press_power_up(ctx, PowerUp::ExtraLife, repeat),
+            event::KeyCode::Key2 |
+            event::KeyCode::Numpad2 if self.phase == Phase::Playing => self.press_power_up(ctx, PowerUp::RemoveWords, repeat),
+            event::KeyCode::Key3 |
+            event::KeyCode::Numpad3 if self.phase == Phase::Playing => self.press_power_up(ctx, PowerUp::SlowSpawn, repeat),
+            event::KeyCode::Key4 |
+            event::KeyCode::Numpad4 if self.phase == Phase::Playing => self.press_power_up(ctx, PowerUp::Shield, repeat),
+            event::KeyCode::Key5 |
+            event::KeyCode::Numpad5 if self.phase == Phase::Playing && !repeat => self.press_panic_button(),
+            event::KeyCode::Key6 |
+            event::KeyCode::Numpad6 if self.phase == Phase::Playing => self.press_power_up(ctx, PowerUp::Magnet, repeat),
+            event::KeyCode::Key0 |
+            event::KeyCode::Numpad0 if self.phase == Phase::Playing => self.undo_purchase(),
+            event::KeyCode::NumpadAdd if self.sound_volume + MainState::SOUND_VOLUME_STEP <= 100.0 => {
+                self.sound_volume += MainState::SOUND_VOLUME_STEP;
+                self.assets.background_music.set_volume(self.sound_volume);
             },
-            event::KeyCode::Y => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "y", "Y")
+            event::KeyCode::NumpadSubtract if self.sound_volume - MainState::SOUND_VOLUME_STEP >= 0.0 => {
+                self.sound_volume -= MainState::SOUND_VOLUME_STEP;
+                self.assets.background_music.set_volume(self.sound_volume);
             },
-            event::KeyCode::Z => {
-                self.current_input = check_shift_pressed(self.current_input.clone(), ctx, "z", "Z")
+            event::KeyCode::F9 => self.set_preset_volume(0),
+            event::KeyCode::F10 => self.set_preset_volume(MainState::VOLUME_PRESETS.len() - 1),
+            event::KeyCode::F1 => self.toggle_color_changing_words(ctx),
+            event::KeyCode::F2 => self.toggle_focus_mode(ctx),
+            event::KeyCode::F3 => self.toggle_speedrun_hud(ctx),
+            event::KeyCode::F11 => self.toggle_fullscreen(ctx),
+            event::KeyCode::Return if self.phase == Phase::GameOver && !self.saved_score => {
+                self.scoreboard = filesystem_helper::save_score(ctx, stats::leaderboard_name(&self.current_input, self.settings.double_speed), self.score, MainState::SCOREBOARD_SIZE, self.ranked)
+                    .unwrap_or_else(|error| {
+                        eprintln!("Couldn't save the score: {}", error);
+                        Vec::new()
+                    });
+                self.current_input = String::new();
+                self.saved_score = true;
             },
+            event::KeyCode::Return if self.phase == Phase::Playing && !self.current_input.is_empty() => self.submit_input(),
             event::KeyCode::Back => {
-                self.current_input.pop();
+                let erased = self.current_input.pop().is_some();
+                if erased && self.phase == Phase::Playing {
+                    self.record_keystroke(stats::BACKSPACE);
+                }
+                if self.current_input.is_empty() {
+                    self.input_burst = false;
+                }
             },
             _ => ()
         }
     }
 
+    // clicking a power up panel buys it, same as its key
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: event::MouseButton, x: f32, y: f32) {
+        if self.phase != Phase::Playing || self.show_info || self.playback.is_some() || button != event::MouseButton::Left {
+            return;
+        }
+
+        let clicked = self.power_up_rects.iter().position(|rect| rect.is_some_and(|rect| rect.contains(Point2 { x, y })));
+        if let Some(index) = clicked {
+            self.press_power_up(ctx, MainState::POWER_UP_ORDER[index], false);
+        }
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        self.apply_screen_size(ctx, width, height);
+    }
+
+    // letters arrive already shifted and in the player's keyboard layout, which keeps non latin words typeable
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) {
+        if self.phase != Phase::Playing && self.phase != Phase::GameOver {
+            return;
+        }
+
+        if character == self.settings.info_key {
+            self.show_info ^= true;
+            return;
+        }
+
+        if character == self.settings.volume_key {
+            self.set_preset_volume(menu::select_next(self.volume_preset, MainState::VOLUME_PRESETS.len()));
+            return;
+        }
+
+        if (self.show_info || self.playback.is_some()) && self.phase == Phase::Playing {
+            return;
+        }
+
+        if self.phase == Phase::Playing {
+            self.started = true;
+        }
+
+        if Word::is_typeable(character) {
+            self.current_input.push(character);
+
+            if self.phase == Phase::Playing {
+                self.chars_this_frame += 1;
+                self.record_keystroke(character);
+            }
+            else if self.saved_score && menu::confirms_restart(&self.current_input) {
+                self.reset(ctx);
+                self.phase = Phase::Playing;
+            }
+        }
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        self.save_before_quit(ctx);
+
+        // false lets the quit go on, a failed save doesn't keep the game open
+        false
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        let background_color = graphics::Color::BLACK;
+        // fades from grey back to black after a milestone
+        let mut flash = 0.25 * self.flash_time / MainState::FLASH_DURATION;
+        if self.settings.breathing_background && self.phase == Phase::Playing {
+            flash = flash.max(draw_helper::breathing_level(self.breath_time, MainState::BREATH_PERIOD, MainState::BREATH_BRIGHTNESS));
+        }
+        let background_color = graphics::Color::new(flash, flash, flash, 1.0);
         graphics::clear(ctx, background_color);
 
+        if self.phase == Phase::Menu || self.phase == Phase::Paused {
+            self.draw_menu(ctx)?;
+            graphics::present(ctx)?;
+            return Ok(())
+        }
+
         let label_margin = 10.0;
         let game_status_panel_color = graphics::Color::WHITE;
         let mut shake_translation: Point2<f32> = Point2 {
@@ -404,42 +2213,99 @@ impl event::EventHandler for MainState {
             shake_translation.y = dy;
         }
 
-        // Draw current user input
-        if !self.game_over || !self.saved_score {
-            let mut bottom_left = Point2 {
-                x: 0.0,
-                y: self.screen_height
+        // Draw the combo timer, it shrinks until the next word has to be typed
+        let combo_time_left = self.economy.combo_window - self.time_since_typed_word;
+        if self.phase == Phase::Playing && self.combo > 0 && combo_time_left > 0.0 {
+            let combo_label = format!("COMBO x{}", self.combo);
+            let mut combo_panel = TextSprite::new(&combo_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            let mut combo_pos = Point2 {
+                x: label_margin,
+                y: self.spawn_band.1 + MainState::WORD_TEXT_SIZE - combo_panel.height(ctx)
             };
-    
-            draw_helper::translate(&mut bottom_left, &shake_translation);
+            draw_helper::translate(&mut combo_pos, &shake_translation);
+            combo_panel.draw(combo_pos, graphics::Color::from_rgb(255, 165, 0), ctx).unwrap();
+
+            let bar_width = MainState::COMBO_BAR_WIDTH * combo_time_left / self.economy.combo_window;
+            let bar = graphics::Rect::new(combo_pos.x + combo_panel.width(ctx) + label_margin, combo_pos.y + combo_panel.height(ctx) / 3.0, bar_width, combo_panel.height(ctx) / 3.0);
+            let bar_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), bar, graphics::Color::from_rgb(255, 165, 0))?;
+            graphics::draw(ctx, &bar_mesh, graphics::DrawParam::default())?;
+        }
+
+        // Draw current user input, the scoreboard takes it as well to start the next run
+        let mut bottom_left = Point2 {
+            x: 0.0,
+            y: self.screen_height
+        };
+
+        draw_helper::translate(&mut bottom_left, &shake_translation);
+
+        let current_input_label = format!("Input: {}", draw_helper::visual_order(&self.current_input));
+        let mut current_input_panel = TextSprite::new(&current_input_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+        bottom_left.x += label_margin;
+        bottom_left.y = self.screen_height - current_input_panel.height(ctx);
+        current_input_panel.draw(bottom_left, game_status_panel_color, ctx).unwrap();
+
+        // Draw the shift and caps lock badge, past where the caret blinks
+        let shift = keyboard::is_mod_active(ctx, event::KeyMods::SHIFT);
+        if let Some(badge_label) = draw_helper::modifier_badge(shift, self.caps_lock) {
+            let mut badge_panel = TextSprite::new(badge_label, self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            let badge_pos = Point2 { x: bottom_left.x + current_input_panel.width(ctx) + 2.0 * label_margin, y: bottom_left.y };
+            badge_panel.draw(badge_pos, graphics::Color::new(1.0, 1.0, 1.0, 0.4), ctx).unwrap();
+        }
+
+        // drawn after the label, so blinking doesn't move anything
+        if self.cursor_timer < MainState::CURSOR_BLINK {
+            let mut caret_panel = TextSprite::new("|", self.assets.font, MainState::BOT_PANEL_TEXT_SIZE);
+            bottom_left.x += current_input_panel.width(ctx);
+            caret_panel.draw(bottom_left, game_status_panel_color, ctx).unwrap();
+        }
+
+        // Draw the lengths of the typed words, one bar per length above the input
+        if self.settings.show_length_histogram || debug::is_active() {
+            self.draw_length_histogram(ctx, bottom_left.y - label_margin)?;
+        }
 
-            let current_input_label = format!("Input: {}", self.current_input);
-            let mut current_input_panel = TextSprite::new(&current_input_label, ctx, MainState::BOT_PANEL_TEXT_SIZE).unwrap();
-            bottom_left.x += label_margin;
-            bottom_left.y = self.screen_height - current_input_panel.height(ctx);
-            current_input_panel.draw(bottom_left, game_status_panel_color, ctx).unwrap();
+        // Draw where every word is on its way to the right edge
+        if self.settings.show_minimap {
+            self.draw_minimap(ctx)?;
         }
 
         // Game over scene
-        if self.game_over {
+        if self.phase == Phase::GameOver {
 
             if !self.saved_score {
-                let ending;
-                if self.score < 100.0 {
-                    ending = "Bummer, I know you can do better :) Try again!";
+                let survived = draw_helper::format_elapsed(self.stats.elapsed);
+                let killed_by = match &self.killer_word {
+                    Some(word) => format!("Killed by : {}\n", draw_helper::visual_order(word)),
+                    None => String::new()
+                };
+                let passage_complete = self.killer_word.is_none() && self.passage.as_ref().is_some_and(|passage| passage.is_complete());
+                let title = if passage_complete { "Passage complete!" } else { "Game over!" };
+                let scoreboard_hint = if self.playback.is_some() {
+                    "End of the replay, press Enter for the scoreboard"
                 }
-                else if self.score >= 100.0 && self.score < 500.0 {
-                    ending = "Not very bad!";
+                else if self.ranked {
+                    "Type username for the scoreboard!"
                 }
-                else if self.score >= 500.0 && self.score < 1000.0 {
-                    ending = "Amazing, but can you do better?"
+                else if debug::is_active() {
+                    "Debug run, it isn't ranked"
                 }
                 else {
-                    ending = "You're a madman, niiice :)"
+                    "Pasted input, the run isn't ranked"
+                };
+                let counts = self.purchase_counts;
+                let purchases = format!("Buffs bought : {} extra life, {} remove words, {} slow spawn, {} shield, {} magnet",
+                                        counts.extra_life, counts.remove_words, counts.slow_spawn, counts.shield, counts.magnet);
+                let length_accuracy = format!("Typed by length : {}", self.stats.length_accuracy.summary());
+                let game_over_label = if self.settings.survival_summary {
+                    format!("{}\n{}You survived : {}\nWords typed : {}\n{}\n{}\n{}\n{}",
+                            title, killed_by, survived, self.stats.typed_words, purchases, length_accuracy, stats::survival_message(self.stats.elapsed), scoreboard_hint)
                 }
-
-                let game_over_label = format!("Game over!\nYour score is : {:.2}\n{}\nType username for the scoreboard!", self.score, ending);
-                let mut game_over_panel = TextSprite::new(&game_over_label, ctx, MainState::CENTER_PANEL_TEXT_SIZE).unwrap();
+                else {
+                    format!("{}\n{}Your score is : {:.2}\nYou survived : {}\n{}\n{}\n{}\n{}",
+                            title, killed_by, self.score, survived, purchases, length_accuracy, stats::flavor_message(self.settings.difficulty, self.stats.typed_words), scoreboard_hint)
+                };
+                let mut game_over_panel = TextSprite::new(&game_over_label, self.assets.font, MainState::CENTER_PANEL_TEXT_SIZE);
 
                 let centered = Point2 {
                     x: (self.screen_width - game_over_panel.width(ctx)) / 2.0,
@@ -449,8 +2315,8 @@ impl event::EventHandler for MainState {
                 game_over_panel.draw(centered, game_status_panel_color, ctx).unwrap();
             }
             else {
-                let scoreboard_label = format!("Scoreboard:\n{}", draw_helper::format_scoreboard(&self.scoreboard));
-                let mut scoreboard_panel = TextSprite::new(&scoreboard_label, ctx, MainState::CENTER_PANEL_TEXT_SIZE).unwrap();
+                let scoreboard_label = format!("Scoreboard:\n{}\nType \"{}\" to play again", draw_helper::format_scoreboard(&self.scoreboard), menu::RESTART_WORD);
+                let mut scoreboard_panel = TextSprite::new(&scoreboard_label, self.assets.font, MainState::CENTER_PANEL_TEXT_SIZE);
 
                 let centered = Point2 {
                     x: (self.screen_width - scoreboard_panel.width(ctx)) / 2.0,
@@ -478,106 +2344,125 @@ impl event::EventHandler for MainState {
             self.info_panel.draw(centered, info_panel_color, ctx)?;
         }
 
-        // Draw current volume
-        let mut top_left = Point2 {
-            x: 0.0,
-            y: 0.0
-        };
-
-        draw_helper::translate(&mut top_left, &shake_translation);
-
-        let options_label = format!("(`) for Info|");
-        let mut options_panel = TextSprite::new(&options_label, ctx, MainState::TOP_PANEL_TEXT_SIZE).unwrap();
-        top_left.x += label_margin;
-        options_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
-        top_left.x += options_panel.width(ctx);
+        if self.settings.speedrun_hud {
+            self.draw_speedrun_hud(ctx, shake_translation)?;
+        }
+        else {
+            self.draw_hud(ctx, shake_translation)?;
+        }
 
-        let current_volume_label = format!("Volume: {:.0}", self.sound_volume * 100.0);
-        let mut current_volume_panel = TextSprite::new(&current_volume_label, ctx, MainState::TOP_PANEL_TEXT_SIZE).unwrap();
-        top_left.x += label_margin;
-        current_volume_panel.draw(top_left, game_status_panel_color, ctx).unwrap();
+        if self.sudden_death {
+            draw_helper::draw_vignette(self.screen_width, self.screen_height, graphics::Color::from_rgb(200, 0, 0), ctx);
+        }
 
-        // Draw current cash
-        let mut bottom_right = Point2 {
-            x: self.screen_width,
-            y: self.screen_height
-        };
+        // Draw the banner of the upcoming wave
+        if self.settings.waves && self.wave_rest > 0.0 && self.passage.is_none() && self.playback.is_none() {
+            let mut wave_panel = TextSprite::new(&format!("Wave {}", self.wave), self.assets.font, MainState::CENTER_PANEL_TEXT_SIZE);
+            let wave_pos = Point2 {
+                x: (self.screen_width - wave_panel.width(ctx)) / 2.0,
+                y: self.screen_height / 3.0
+            };
 
-        draw_helper::translate(&mut bottom_right, &shake_translation);
+            wave_panel.draw(wave_pos, Word::DEFAULT_COLOR, ctx).unwrap();
+        }
 
-        let cash_label = format!("Cash: {:.2}", self.cash);
-        let mut cash_panel = TextSprite::new(&cash_label, ctx, MainState::BOT_PANEL_TEXT_SIZE).unwrap();
-        bottom_right.x -= cash_panel.width(ctx) + label_margin;
-        bottom_right.y -= cash_panel.height(ctx);
-        cash_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
-        bottom_right.y += cash_panel.height(ctx);
+        // Draw the milestone toast
+        if let Some(toast_label) = &self.toast_label {
+            let mut toast_panel = TextSprite::new(toast_label, self.assets.font, MainState::CENTER_PANEL_TEXT_SIZE);
+            let toast_pos = Point2 {
+                x: (self.screen_width - toast_panel.width(ctx)) / 2.0,
+                y: self.screen_height / 4.0
+            };
 
-        // Draw remaining lifes
-        let lifes_label = format!("Lifes: {}", self.remaining_lifes);
-        let mut lifes_panel = TextSprite::new(&lifes_label, ctx, MainState::BOT_PANEL_TEXT_SIZE).unwrap();
-        bottom_right.x -= lifes_panel.width(ctx) + label_margin;
-        bottom_right.y -= lifes_panel.height(ctx);
-        lifes_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
-        bottom_right.y += lifes_panel.height(ctx);
+            toast_panel.draw(toast_pos, Word::TYPED_COLOR, ctx).unwrap();
+        }
 
-        // Draw current score
-        let score_label = format!("Score: {:.2}", self.score);
-        let mut score_panel = TextSprite::new(&score_label, ctx, MainState::BOT_PANEL_TEXT_SIZE).unwrap();
-        bottom_right.x -= score_panel.width(ctx) + label_margin;
-        bottom_right.y -= score_panel.height(ctx);
-        score_panel.draw(bottom_right, game_status_panel_color, ctx).unwrap();
-        bottom_right.y += score_panel.height(ctx);
+        if !self.started {
+            let mut start_panel = TextSprite::new("Press any key to begin", self.assets.font, MainState::CENTER_PANEL_TEXT_SIZE);
+            let start_pos = Point2 {
+                x: (self.screen_width - start_panel.width(ctx)) / 2.0,
+                y: (self.screen_height - start_panel.height(ctx)) / 2.0
+            };
 
-        // Draw power ups
-        let power_up_color = graphics::Color::WHITE;
-        let mut top_right = Point2 {
-            x: self.screen_width,
-            y: 0.0
-        };
+            start_panel.draw(start_pos, Word::DEFAULT_COLOR, ctx).unwrap();
+        }
 
-        draw_helper::translate(&mut top_right, &shake_translation);
+        // Draw the ghost of the next word
+        if self.settings.next_word_preview {
+            if let Some(next_word) = &self.next_word {
+                let mut ghost_pos = Point2 {
+                    x: label_margin,
+                    y: MainState::TOP_PANEL_TEXT_SIZE + label_margin
+                };
 
-        if self.cash >= MainState::SLOW_WORD_SPAWN_TAX {
-            top_right.x -= self.power_up_panels[0].width(ctx) + label_margin;
-            self.power_up_panels[0].draw(top_right, power_up_color, ctx).unwrap();
-        }
+                draw_helper::translate(&mut ghost_pos, &shake_translation);
 
-        if self.cash >= MainState::REMOVE_WORDS_TAX {
-            top_right.x -= self.power_up_panels[1].width(ctx) + label_margin;
-            self.power_up_panels[1].draw(top_right, power_up_color, ctx).unwrap();
+                let ghost_label = format!("Next: {}", next_word);
+                let mut ghost_panel = TextSprite::new(&ghost_label, self.assets.font, MainState::WORD_TEXT_SIZE);
+                ghost_panel.draw(ghost_pos, graphics::Color::new(1.0, 1.0, 1.0, 0.3), ctx).unwrap();
+            }
         }
 
-        if self.cash >= MainState::BUY_LIFE_TAX {
-            top_right.x -= self.power_up_panels[2].width(ctx) + label_margin;
-            self.power_up_panels[2].draw(top_right, power_up_color, ctx).unwrap();
+        // Draw the snap of the last magnet pull, fading out behind the pulled word
+        if let Some((trail, time_left)) = self.magnet_trail {
+            let alpha = 0.35 * time_left / MainState::MAGNET_TRAIL_DURATION;
+            let trail_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), trail, graphics::Color::new(0.55, 0.75, 1.0, alpha))?;
+            graphics::draw(ctx, &trail_mesh, graphics::DrawParam::default())?;
         }
 
-        for word in self.words.iter_mut() {
+        let focused = if self.settings.focus_mode { entities::most_urgent(&self.words) } else { None };
+        let escape_line = self.escape_line();
+        let mut word_shapes = RectBatch::new();
+        for (index, word) in self.words.iter_mut().enumerate() {
+            word.is_dimmed = focused.is_some() && focused != Some(index);
+            word.is_color_frozen = !self.settings.color_changing_words;
             word.translate(shake_translation);
 
             if !self.shake_screen {
                 word.reset_translation();
             }
 
-            word.draw(ctx)?;
+            word.queue(ctx, &mut word_shapes)?;
+
+            // queued behind the word, so it's part of the same draw call
+            if self.settings.show_escape_times && !word.is_typed {
+                let rect = word.bounding_rect(ctx);
+                if let Some(seconds) = word.time_to_escape(escape_line, rect.w) {
+                    let mut time_panel = TextSprite::new(&format!("{:.1}s", seconds), self.assets.font, MainState::ESCAPE_TIME_TEXT_SIZE);
+                    let time_pos = Point2 { x: rect.right() + 4.0, y: rect.y };
+                    time_panel.queue(time_pos, graphics::Color::new(1.0, 1.0, 1.0, 0.6), ctx)?;
+                }
+            }
+        }
+        // one draw call for the underlines of all the words and one for their texts, the underlines go first so the texts stay on top
+        word_shapes.draw(ctx)?;
+        graphics::draw_queued_text(ctx, graphics::DrawParam::default(), None, graphics::FilterMode::Linear)?;
+
+        // Draw the last typed words, in the space the words don't get to
+        if self.settings.typed_log {
+            let separator = graphics::Rect::new(self.escape_line(), self.spawn_band.0, 1.0, self.spawn_band.1 - self.spawn_band.0);
+            let separator_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), separator, graphics::Color::new(1.0, 1.0, 1.0, 0.2))?;
+            graphics::draw(ctx, &separator_mesh, graphics::DrawParam::default())?;
+
+            let log_label = self.typed_log.iter().map(String::as_str).collect::<Vec<&str>>().join("\n");
+            let mut log_panel = TextSprite::new(&log_label, self.assets.font, MainState::POPUP_TEXT_SIZE);
+            let log_pos = Point2 { x: self.escape_line() + label_margin, y: self.spawn_band.0 };
+            log_panel.draw(log_pos, graphics::Color::new(1.0, 1.0, 1.0, 0.6), ctx)?;
+        }
+
+        for popup in &self.point_popups {
+            let mut popup_panel = TextSprite::new(&popup.text, self.assets.font, MainState::POPUP_TEXT_SIZE);
+            let mut color = popup.color;
+            color.a *= popup.alpha();
+            popup_panel.draw(popup.pos, color, ctx)?;
         }
 
         if debug::is_active() {
-            for word in &mut self.words {
-                debug::draw_outline(word.bounding_rect(ctx), ctx).unwrap();
-            }
+            let outlines = self.words.iter().map(|word| word.bounding_rect(ctx)).collect::<Vec<graphics::Rect>>();
+            debug::draw_outlines(&outlines, ctx)?;
         }
 
         graphics::present(ctx)?;
         Ok(())
     }
-}
-
-fn check_shift_pressed(current_input: String, ctx: &mut Context, lower_letter: &str, upper_letter: &str) -> String {
-    if is_key_pressed(ctx, event::KeyCode::LShift) ||
-       is_key_pressed(ctx, event::KeyCode::RShift) {
-        return current_input + upper_letter;
-    }
-
-    current_input + lower_letter
 }
\ No newline at end of file
@@ -0,0 +1,34 @@
+// selection helpers for the keyboard driven menus, moving past either end wraps around
+
+pub fn select_next(selected_index: usize, options_count: usize) -> usize {
+    if options_count == 0 {
+        return 0;
+    }
+
+    (selected_index + 1) % options_count
+}
+
+pub fn select_previous(selected_index: usize, options_count: usize) -> usize {
+    if options_count == 0 {
+        return 0;
+    }
+
+    (selected_index + options_count - 1) % options_count
+}
+
+// first and one past the last option on screen, scrolled so the selected one stays around the middle
+pub fn visible_range(selected_index: usize, options_count: usize, max_visible: usize) -> (usize, usize) {
+    if options_count <= max_visible {
+        return (0, options_count);
+    }
+
+    let start = selected_index.saturating_sub(max_visible / 2).min(options_count - max_visible);
+    (start, start + max_visible)
+}
+
+// typed on the scoreboard to start the next run, a reflex key press can't skip the screen
+pub const RESTART_WORD: &str = "again";
+
+pub fn confirms_restart(input: &str) -> bool {
+    input.to_lowercase() == RESTART_WORD
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+// words of a passage file, spawned and typed strictly in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    words: Vec<String>,
+    spawned: usize,
+    finished: usize
+}
+
+impl Passage {
+    pub const DIR: &'static str = "/passages";
+
+    pub fn from_text(text: &str) -> Passage {
+        Passage {
+            words: text.split_whitespace().map(String::from).collect(),
+            spawned: 0,
+            finished: 0
+        }
+    }
+
+    // none once every word is on its way
+    pub fn spawn_next(&mut self) -> Option<String> {
+        let word = self.words.get(self.spawned).cloned();
+        if word.is_some() {
+            self.spawned += 1;
+        }
+
+        word
+    }
+
+    // typed, removed or escaped, every finished word moves the passage forward
+    pub fn finish_word(&mut self) {
+        self.finished = (self.finished + 1).min(self.spawned);
+    }
+
+    // a removed word that came back on screen
+    pub fn unfinish_word(&mut self) {
+        self.finished = self.finished.saturating_sub(1);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.finished == self.words.len()
+    }
+
+    // finished and total words
+    pub fn progress(&self) -> (usize, usize) {
+        (self.finished, self.words.len())
+    }
+
+    // every "*.txt" file of the passages dir, sorted by name
+    pub fn passages_from(paths: Vec<PathBuf>) -> Vec<String> {
+        let mut passages = paths.iter()
+            .filter_map(|path| path.to_str())
+            .filter(|path| path.ends_with(".txt"))
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        passages.sort();
+        passages.dedup();
+        passages
+    }
+}
@@ -0,0 +1,261 @@
+use rand::Rng;
+use rand::seq;
+use serde::{ Deserialize, Serialize };
+
+use crate::economy::{ Economy, Wallet };
+use crate::entities::Word;
+use crate::passage::Passage;
+use crate::stats::RunStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PowerUp {
+    ExtraLife,
+    RemoveWords,
+    SlowSpawn,
+    Shield,
+    // pulls the word closest to escaping back towards the left edge
+    Magnet
+}
+
+// what an escaped word takes away
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EscapeCost {
+    // the warmup at the start of a run and the panic button forgive the escapes
+    Free,
+    Shield,
+    Life
+}
+
+impl PowerUp {
+    pub fn tax(&self, economy: &Economy) -> f32 {
+        match self {
+            PowerUp::ExtraLife => economy.buy_life_tax,
+            PowerUp::RemoveWords => economy.remove_words_tax,
+            PowerUp::SlowSpawn => economy.slow_word_spawn_tax,
+            PowerUp::Shield => economy.shield_tax,
+            PowerUp::Magnet => economy.magnet_tax
+        }
+    }
+
+    // the tax after `purchases` earlier buys of the same power up
+    pub fn price(&self, economy: &Economy, purchases: u32) -> f32 {
+        escalated_tax(self.tax(economy), purchases)
+    }
+
+    // takes the price out of the wallet, a purchase the player can't afford leaves it untouched
+    pub fn charge(&self, economy: &Economy, purchases: u32, wallet: &mut Wallet) -> bool {
+        wallet.spend(self.price(economy, purchases))
+    }
+}
+
+// buys of every power up during a run, an undone purchase doesn't count
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PurchaseCounts {
+    pub extra_life: u32,
+    pub remove_words: u32,
+    pub slow_spawn: u32,
+    pub shield: u32,
+    pub magnet: u32
+}
+
+impl PurchaseCounts {
+    pub fn count(&self, power_up: PowerUp) -> u32 {
+        match power_up {
+            PowerUp::ExtraLife => self.extra_life,
+            PowerUp::RemoveWords => self.remove_words,
+            PowerUp::SlowSpawn => self.slow_spawn,
+            PowerUp::Shield => self.shield,
+            PowerUp::Magnet => self.magnet
+        }
+    }
+
+    pub fn record(&mut self, power_up: PowerUp) {
+        *self.count_mut(power_up) += 1;
+    }
+
+    pub fn undo(&mut self, power_up: PowerUp) {
+        let count = self.count_mut(power_up);
+        *count = count.saturating_sub(1);
+    }
+
+    fn count_mut(&mut self, power_up: PowerUp) -> &mut u32 {
+        match power_up {
+            PowerUp::ExtraLife => &mut self.extra_life,
+            PowerUp::RemoveWords => &mut self.remove_words,
+            PowerUp::SlowSpawn => &mut self.slow_spawn,
+            PowerUp::Shield => &mut self.shield,
+            PowerUp::Magnet => &mut self.magnet
+        }
+    }
+}
+
+// the whole cash went into clearing the board, the words are handed back for the stats
+#[derive(Debug)]
+pub struct PanicClear<T> {
+    pub spent: f32,
+    pub cleared: Vec<T>
+}
+
+// empties the wallet and the board together, or leaves both untouched
+// the cash has to reach `min_cash` and there has to be a word to clear
+pub fn panic_clear<T>(wallet: &mut Wallet, words: &mut Vec<T>, min_cash: f32) -> Option<PanicClear<T>> {
+    let spent = wallet.cash();
+    if words.is_empty() || spent <= 0.0 || spent < min_cash || !wallet.spend(spent) {
+        return None;
+    }
+
+    Some(PanicClear { spent, cleared: std::mem::take(words) })
+}
+
+// seconds after a press of a power up key during which the next press of the same one is ignored
+pub const PURCHASE_COOLDOWN: f32 = 0.3;
+
+// one press of a power up key buys once, the key repeat of a held key and a bounce right after are ignored
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PurchaseThrottle {
+    last_presses: [Option<f32>; 5]
+}
+
+impl PurchaseThrottle {
+    pub fn new() -> Self {
+        PurchaseThrottle::default()
+    }
+
+    // `now` is on the run clock, true when the press should go on to the purchase
+    pub fn press(&mut self, power_up: PowerUp, now: f32, repeat: bool) -> bool {
+        if repeat {
+            return false;
+        }
+
+        let last_press = &mut self.last_presses[PurchaseThrottle::slot(power_up)];
+        if last_press.is_some_and(|time| now - time < PURCHASE_COOLDOWN) {
+            return false;
+        }
+
+        *last_press = Some(now);
+        true
+    }
+
+    fn slot(power_up: PowerUp) -> usize {
+        match power_up {
+            PowerUp::ExtraLife => 0,
+            PowerUp::RemoveWords => 1,
+            PowerUp::SlowSpawn => 2,
+            PowerUp::Shield => 3,
+            PowerUp::Magnet => 4
+        }
+    }
+}
+
+// every buy adds this share of the base tax to the next one
+pub const PRICE_ESCALATION: f32 = 0.5;
+
+pub fn escalated_tax(tax: f32, purchases: u32) -> f32 {
+    tax * (1.0 + PRICE_ESCALATION * purchases as f32)
+}
+
+// an active shield always goes before a life, and it keeps the streak going
+pub fn escape_cost(shield_active: bool, warmup_left: f32) -> EscapeCost {
+    if warmup_left > 0.0 {
+        EscapeCost::Free
+    }
+    else if shield_active {
+        EscapeCost::Shield
+    }
+    else {
+        EscapeCost::Life
+    }
+}
+
+// the slow spawn buff takes back half of the speed up gathered so far
+pub fn slowed_speed_up(speed_up: f32) -> f32 {
+    speed_up / 2.0
+}
+
+// seconds the timed slow spawn lasts before the speed up it took comes back
+pub const SLOW_SPAWN_DURATION: f32 = 20.0;
+
+// the ramp keeps going during a timed slow spawn, only what the buff took is given back
+pub fn restored_speed_up(speed_up: f32, taken: f32) -> f32 {
+    speed_up + taken
+}
+
+// indexes of the words the removal buff takes, all of them when there aren't more than `count`
+pub fn removal_indexes<R: Rng>(rng: &mut R, words_count: usize, count: usize) -> Vec<usize> {
+    if words_count <= count {
+        return (0 .. words_count).collect();
+    }
+
+    seq::index::sample(rng, words_count, count).into_vec()
+}
+
+// words taken off the board by one removal buff
+pub const REMOVE_WORDS_COUNT: usize = 2;
+
+// the buys below leave everything untouched when the wallet can't pay the price
+
+pub fn buy_extra_life(economy: &Economy, purchases: u32, wallet: &mut Wallet, remaining_lifes: &mut u32) -> bool {
+    if !PowerUp::ExtraLife.charge(economy, purchases, wallet) {
+        return false;
+    }
+
+    *remaining_lifes += 1;
+    true
+}
+
+// one shield at a time
+pub fn buy_shield(economy: &Economy, purchases: u32, wallet: &mut Wallet, shield_active: &mut bool) -> bool {
+    if *shield_active || !PowerUp::Shield.charge(economy, purchases, wallet) {
+        return false;
+    }
+
+    *shield_active = true;
+    true
+}
+
+// the removed words with their old index and the reward they paid, from the back of the board to the front
+// nothing is bought for an empty board
+pub fn buy_remove_words<R: Rng>(rng: &mut R, economy: &Economy, purchases: u32, wallet: &mut Wallet, words: &mut Vec<Word>, stats: &mut RunStats, mut passage: Option<&mut Passage>) -> Vec<(usize, Word, f32)> {
+    if words.is_empty() || !PowerUp::RemoveWords.charge(economy, purchases, wallet) {
+        return Vec::new();
+    }
+
+    // taken out back to front, so the remaining indexes stay valid
+    let mut indexes = removal_indexes(rng, words.len(), REMOVE_WORDS_COUNT);
+    indexes.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut removed = Vec::new();
+    for index in indexes {
+        let word = words.remove(index);
+        stats.record_removed(word.label());
+        if let Some(passage) = passage.as_deref_mut() {
+            passage.finish_word();
+        }
+
+        let reward = word.get_reward(economy);
+        removed.push((index, word, reward));
+    }
+
+    removed
+}
+
+// what a slow spawn buy changed, to undo it or to give the speed up back once a timed one wears off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlowSpawnBuy {
+    pub speed_up_before: f32,
+    pub speed_up: f32,
+    // 0 for a slow spawn that lasts the whole run
+    pub duration: f32,
+    pub taken: f32
+}
+
+pub fn buy_slow_spawn(economy: &Economy, purchases: u32, wallet: &mut Wallet, speed_up: f32, timed: bool) -> Option<SlowSpawnBuy> {
+    if !PowerUp::SlowSpawn.charge(economy, purchases, wallet) {
+        return None;
+    }
+
+    let slowed = slowed_speed_up(speed_up);
+    let (duration, taken) = if timed { (SLOW_SPAWN_DURATION, speed_up - slowed) } else { (0.0, 0.0) };
+
+    Some(SlowSpawnBuy { speed_up_before: speed_up, speed_up: slowed, duration, taken })
+}
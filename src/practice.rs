@@ -0,0 +1,89 @@
+use ggez:: { filesystem, Context };
+use rand::Rng;
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::filesystem_helper;
+
+// share of the practice spawns drawn from the missed words, the rest keeps the dictionary in the mix
+pub const PRACTICE_CHANCE: u8 = 75;
+
+// how often every word escaped, summed over all the runs and saved as "word count" lines
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MissedWords {
+    counts: BTreeMap<String, u32>
+}
+
+impl MissedWords {
+    pub const FILE_PATH: &'static str = "/missed_words.data";
+
+    pub fn new() -> Self {
+        MissedWords::default()
+    }
+
+    pub fn load(ctx: &Context) -> MissedWords {
+        if !filesystem::exists(ctx, MissedWords::FILE_PATH) {
+            return MissedWords::new();
+        }
+
+        MissedWords::from_lines(&filesystem_helper::read_file_by_lines(ctx, MissedWords::FILE_PATH))
+    }
+
+    pub fn save(&self, ctx: &Context) {
+        if let Ok(mut file) = filesystem::create(ctx, MissedWords::FILE_PATH) {
+            let _ = file.write(self.to_lines().join("\n").as_bytes());
+        }
+    }
+
+    // malformed lines are skipped
+    pub fn from_lines(lines: &[String]) -> MissedWords {
+        let mut missed = MissedWords::new();
+
+        for line in lines {
+            let split = line.split_whitespace().collect::<Vec<&str>>();
+            if let [word, count] = split.as_slice() {
+                if let Ok(count) = count.parse::<u32>() {
+                    *missed.counts.entry(word.to_string()).or_insert(0) += count;
+                }
+            }
+        }
+
+        missed
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        self.counts.iter().map(|(word, count)| format!("{} {}", word, count)).collect()
+    }
+
+    pub fn record(&mut self, word: &str) {
+        *self.counts.entry(String::from(word)).or_insert(0) += 1;
+    }
+
+    pub fn count(&self, word: &str) -> u32 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // the more often a word escaped, the more likely it comes back
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> Option<String> {
+        let total = self.counts.values().sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0 .. total);
+        for (word, count) in &self.counts {
+            if roll < *count {
+                return Some(word.clone());
+            }
+
+            roll -= count;
+        }
+
+        None
+    }
+}
\ No newline at end of file
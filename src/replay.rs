@@ -0,0 +1,123 @@
+use serde::{ Deserialize, Serialize };
+
+use crate::power_up::PowerUp;
+
+// everything a run needs to be played again, the times are on the run clock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    Spawn {
+        time: f32,
+        label: String,
+        y: f32,
+        speed: f32,
+        is_color_changing: bool,
+        // missing from the recordings made before words could shrink
+        #[serde(default)]
+        is_shrinking: bool
+    },
+    Key { time: f32, key: char },
+    // a press of a power up key or panel that got past the throttle, bought or not
+    PowerUp { time: f32, power_up: PowerUp },
+    Undo { time: f32 },
+    Panic { time: f32 }
+}
+
+impl ReplayEvent {
+    pub fn time(&self) -> f32 {
+        match self {
+            ReplayEvent::Spawn { time, .. } => *time,
+            ReplayEvent::Key { time, .. } => *time,
+            ReplayEvent::PowerUp { time, .. } => *time,
+            ReplayEvent::Undo { time } => *time,
+            ReplayEvent::Panic { time } => *time
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    events: Vec<ReplayEvent>,
+    // picks the words the removal buff takes, so the replay removes the same ones
+    // missing from the recordings made before the buffs were recorded
+    #[serde(default)]
+    removal_seed: u64
+}
+
+impl Recording {
+    pub const FILE_NAME: &'static str = "replay.json";
+
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    pub fn record_spawn(&mut self, time: f32, label: &str, y: f32, speed: f32, is_color_changing: bool, is_shrinking: bool) {
+        self.events.push(ReplayEvent::Spawn { time, label: String::from(label), y, speed, is_color_changing, is_shrinking });
+    }
+
+    pub fn record_key(&mut self, time: f32, key: char) {
+        self.events.push(ReplayEvent::Key { time, key });
+    }
+
+    pub fn record_power_up(&mut self, time: f32, power_up: PowerUp) {
+        self.events.push(ReplayEvent::PowerUp { time, power_up });
+    }
+
+    pub fn record_undo(&mut self, time: f32) {
+        self.events.push(ReplayEvent::Undo { time });
+    }
+
+    pub fn record_panic(&mut self, time: f32) {
+        self.events.push(ReplayEvent::Panic { time });
+    }
+
+    pub fn removal_seed(&self) -> u64 {
+        self.removal_seed
+    }
+
+    pub fn set_removal_seed(&mut self, seed: u64) {
+        self.removal_seed = seed;
+    }
+
+    pub fn events(&self) -> &[ReplayEvent] {
+        &self.events
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Recording> {
+        serde_json::from_str(json)
+    }
+}
+
+// hands the recorded events back in order, as the run clock reaches them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    recording: Recording,
+    next: usize
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Playback { recording, next: 0 }
+    }
+
+    pub fn due(&mut self, time: f32) -> Vec<ReplayEvent> {
+        let start = self.next;
+        let events = &self.recording.events;
+        while self.next < events.len() && events[self.next].time() <= time {
+            self.next += 1;
+        }
+
+        events[start .. self.next].to_vec()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.events.len()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use serde::{ Deserialize, Serialize };
+
+use crate::difficulty::Difficulty;
+use crate::power_up::PurchaseCounts;
+use crate::stats::RunStats;
+
+// a word on the board, its sprite is made again when the session is resumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordSnapshot {
+    pub label: String,
+    pub x: f32,
+    pub y: f32,
+    pub speed: f32,
+    pub is_color_changing: bool,
+    pub is_shrinking: bool
+}
+
+// the simulation side of a run that was interrupted by quitting, the input and the effects start over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub version: u32,
+    pub difficulty: Difficulty,
+    pub words: Vec<WordSnapshot>,
+    pub next_word: Option<String>,
+    pub cash: f32,
+    pub score: f32,
+    pub remaining_lifes: u32,
+    pub shield_active: bool,
+    pub ranked: bool,
+    pub warmup_time: f32,
+    pub time_until_next_word: f32,
+    pub game_speed_up: f32,
+    pub time_until_shake: f32,
+    pub slow_spawn_time: f32,
+    pub slow_spawn_taken: f32,
+    pub wave: u32,
+    pub wave_spawns_left: usize,
+    pub wave_rest: f32,
+    pub wave_escaped: bool,
+    pub spawned_words: u32,
+    pub purchases: PurchaseCounts,
+    pub stats: RunStats
+}
+
+impl SessionSnapshot {
+    // bumped whenever the fields change, the saves of other versions are discarded
+    pub const VERSION: u32 = 2;
+    pub const FILE_NAME: &'static str = "session.json";
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    // none for a save that can't be read or comes from another version
+    pub fn from_json(json: &str) -> Option<SessionSnapshot> {
+        serde_json::from_str::<SessionSnapshot>(json).ok().filter(|snapshot| snapshot.version == SessionSnapshot::VERSION)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,300 @@
+use ggez:: { filesystem, Context };
+
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::assets::Assets;
+use crate::difficulty::Difficulty;
+use crate::entities::Word;
+use crate::filesystem_helper;
+
+const SETTINGS_PATH: &str = "/settings.data";
+
+// player preferences, saved next to the scoreboard as "key value" lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub color_changing_words: bool,
+    pub survival_summary: bool,
+    pub difficulty: Difficulty,
+    pub next_word_preview: bool,
+    pub heart_lifes: bool,
+    pub starting_cash: f32,
+    pub show_kps: bool,
+    // typed character that opens the info panel, it can't be typed into words anymore
+    pub info_key: char,
+    // typed character that cycles through the volume presets
+    pub volume_key: char,
+    // off by default, weighted dictionaries repeat words on purpose
+    pub dedup_words: bool,
+    pub focus_mode: bool,
+    pub keystroke_log: bool,
+    pub music_track: String,
+    // the spawn gap follows the player's wpm
+    pub adaptive_spawn: bool,
+    // passage file to type in order, empty plays the regular dictionary words
+    pub passage: String,
+    // off keeps the spawn gap constant for the whole run
+    pub speed_ramp: bool,
+    pub fullscreen: bool,
+    // longest word in characters, 0 keeps every word
+    pub max_word_len: usize,
+    // more typed characters than this in one frame is treated as a paste, 0 turns the guard off
+    pub max_chars_per_frame: usize,
+    // slowly pulses the background instead of flat black
+    pub breathing_background: bool,
+    // some words lose their last character while they wait to be typed
+    pub shrinking_words: bool,
+    // random spawn positions tried before a word is allowed to overlap another one
+    pub spawn_retries: usize,
+    // off keeps the half typed input when a word costs a life
+    pub clear_input_on_escape: bool,
+    // challenge modifier on top of the difficulty
+    pub double_speed: bool,
+    pub show_cash_rate: bool,
+    // the same word never spawns twice in a row
+    pub avoid_repeats: bool,
+    // every buy makes the next one of the same power up pricier, for the run
+    pub escalating_prices: bool,
+    pub show_length_histogram: bool,
+    // the slow spawn buff wears off after a while instead of lasting the whole run
+    pub timed_slow_spawn: bool,
+    // nothing spawns and no life is lost before the first keystroke of a run
+    pub safe_start: bool,
+    // no new word spawns at the last life until the board is cleared
+    pub last_stand: bool,
+    // cash taken by an (Enter) that submits input matching no word, 0 turns it off
+    pub miss_penalty: f32,
+    // strip along the bottom edge with a tick for every word on the board
+    pub show_minimap: bool,
+    // most spawns come from the words that escaped in the earlier runs
+    pub practice_weak_words: bool,
+    // side panel with the last typed words, the words escape at its left edge
+    pub typed_log: bool,
+    // most words on the board at once, 0 lets the difficulty decide
+    pub max_visible_words: usize,
+    // the words come in growing waves with a rest in between, instead of a steady stream
+    pub waves: bool,
+    // the latest cash change shows up above the cash for a moment
+    pub show_cash_delta: bool,
+    // submitting input that matches no word costs a life
+    pub strict_submit: bool,
+    // the first words of a run move slower, to get going
+    pub first_words_grace: bool,
+    // quitting in the middle of a run saves it, to be resumed from the main menu
+    pub resume_session: bool,
+    // least cash for the panic button, which trades all of it for an empty board, 0 turns it off
+    pub panic_min_cash: f32,
+    // the seconds left before every word escapes, drawn next to it
+    pub show_escape_times: bool,
+    // the difficulty, the lifes and the spawn rate are picked from the saved scores
+    pub auto_difficulty: bool,
+    // a single block with the typing stats instead of the regular hud, for streaming
+    pub speedrun_hud: bool,
+    // rows the words spawn on, 0 spawns them at any height
+    pub spawn_lanes: usize
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            color_changing_words: true,
+            survival_summary: false,
+            difficulty: Difficulty::Normal,
+            next_word_preview: false,
+            heart_lifes: false,
+            starting_cash: 0.0,
+            show_kps: false,
+            info_key: '`',
+            volume_key: '=',
+            dedup_words: false,
+            focus_mode: false,
+            keystroke_log: false,
+            music_track: String::from(Assets::DEFAULT_MUSIC_TRACK),
+            adaptive_spawn: false,
+            passage: String::new(),
+            speed_ramp: true,
+            fullscreen: false,
+            max_word_len: 0,
+            max_chars_per_frame: 4,
+            breathing_background: false,
+            shrinking_words: false,
+            spawn_retries: 10,
+            clear_input_on_escape: false,
+            double_speed: false,
+            show_cash_rate: false,
+            avoid_repeats: true,
+            escalating_prices: false,
+            show_length_histogram: false,
+            timed_slow_spawn: false,
+            safe_start: false,
+            last_stand: false,
+            miss_penalty: 0.0,
+            show_minimap: false,
+            practice_weak_words: false,
+            typed_log: false,
+            max_visible_words: 0,
+            waves: false,
+            show_cash_delta: true,
+            strict_submit: false,
+            first_words_grace: false,
+            resume_session: false,
+            panic_min_cash: 0.0,
+            show_escape_times: false,
+            auto_difficulty: false,
+            speedrun_hud: false,
+            spawn_lanes: 0
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(ctx: &Context) -> Settings {
+        if !filesystem::exists(ctx, SETTINGS_PATH) {
+            return Settings::default();
+        }
+
+        Settings::from_lines(&filesystem_helper::read_file_by_lines(ctx, SETTINGS_PATH))
+    }
+
+    pub fn save(&self, ctx: &Context) {
+        if let Ok(mut file) = filesystem::create(ctx, SETTINGS_PATH) {
+            let _ = file.write(self.to_lines().join("\n").as_bytes());
+        }
+    }
+
+    // unknown keys and malformed values are skipped so old files keep loading
+    pub fn from_lines(lines: &[String]) -> Settings {
+        let mut settings = Settings::default();
+
+        for line in lines {
+            let split = line.split_whitespace().collect::<Vec<&str>>();
+            if split.len() != 2 {
+                continue;
+            }
+
+            let value = split[1];
+            match split[0] {
+                "color_changing_words" => parse_into(value, &mut settings.color_changing_words),
+                "survival_summary" => parse_into(value, &mut settings.survival_summary),
+                "difficulty" => parse_into(value, &mut settings.difficulty),
+                "next_word_preview" => parse_into(value, &mut settings.next_word_preview),
+                "heart_lifes" => parse_into(value, &mut settings.heart_lifes),
+                "starting_cash" => parse_into(value, &mut settings.starting_cash),
+                "show_kps" => parse_into(value, &mut settings.show_kps),
+                "info_key" => parse_key_into(value, &mut settings.info_key),
+                "volume_key" => parse_key_into(value, &mut settings.volume_key),
+                "dedup_words" => parse_into(value, &mut settings.dedup_words),
+                "focus_mode" => parse_into(value, &mut settings.focus_mode),
+                "keystroke_log" => parse_into(value, &mut settings.keystroke_log),
+                "music_track" => parse_into(value, &mut settings.music_track),
+                "adaptive_spawn" => parse_into(value, &mut settings.adaptive_spawn),
+                "passage" => parse_into(value, &mut settings.passage),
+                "speed_ramp" => parse_into(value, &mut settings.speed_ramp),
+                "fullscreen" => parse_into(value, &mut settings.fullscreen),
+                "max_word_len" => parse_into(value, &mut settings.max_word_len),
+                "max_chars_per_frame" => parse_into(value, &mut settings.max_chars_per_frame),
+                "breathing_background" => parse_into(value, &mut settings.breathing_background),
+                "shrinking_words" => parse_into(value, &mut settings.shrinking_words),
+                "spawn_retries" => parse_into(value, &mut settings.spawn_retries),
+                "clear_input_on_escape" => parse_into(value, &mut settings.clear_input_on_escape),
+                "double_speed" => parse_into(value, &mut settings.double_speed),
+                "show_cash_rate" => parse_into(value, &mut settings.show_cash_rate),
+                "avoid_repeats" => parse_into(value, &mut settings.avoid_repeats),
+                "escalating_prices" => parse_into(value, &mut settings.escalating_prices),
+                "show_length_histogram" => parse_into(value, &mut settings.show_length_histogram),
+                "timed_slow_spawn" => parse_into(value, &mut settings.timed_slow_spawn),
+                "safe_start" => parse_into(value, &mut settings.safe_start),
+                "last_stand" => parse_into(value, &mut settings.last_stand),
+                "miss_penalty" => parse_into(value, &mut settings.miss_penalty),
+                "show_minimap" => parse_into(value, &mut settings.show_minimap),
+                "practice_weak_words" => parse_into(value, &mut settings.practice_weak_words),
+                "typed_log" => parse_into(value, &mut settings.typed_log),
+                "max_visible_words" => parse_into(value, &mut settings.max_visible_words),
+                "waves" => parse_into(value, &mut settings.waves),
+                "show_cash_delta" => parse_into(value, &mut settings.show_cash_delta),
+                "strict_submit" => parse_into(value, &mut settings.strict_submit),
+                "first_words_grace" => parse_into(value, &mut settings.first_words_grace),
+                "resume_session" => parse_into(value, &mut settings.resume_session),
+                "panic_min_cash" => parse_into(value, &mut settings.panic_min_cash),
+                "show_escape_times" => parse_into(value, &mut settings.show_escape_times),
+                "auto_difficulty" => parse_into(value, &mut settings.auto_difficulty),
+                "speedrun_hud" => parse_into(value, &mut settings.speedrun_hud),
+                "spawn_lanes" => parse_into(value, &mut settings.spawn_lanes),
+                _ => ()
+            }
+        }
+
+        // one key can't do both, the info panel keeps it and the volume gets a default the info key doesn't use
+        if settings.volume_key == settings.info_key {
+            settings.volume_key = if settings.info_key == Settings::default().volume_key { Settings::default().info_key } else { Settings::default().volume_key };
+        }
+
+        settings
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        vec![
+            format!("color_changing_words {}", self.color_changing_words),
+            format!("survival_summary {}", self.survival_summary),
+            format!("difficulty {}", self.difficulty),
+            format!("next_word_preview {}", self.next_word_preview),
+            format!("heart_lifes {}", self.heart_lifes),
+            format!("starting_cash {}", self.starting_cash),
+            format!("show_kps {}", self.show_kps),
+            format!("info_key {}", self.info_key),
+            format!("volume_key {}", self.volume_key),
+            format!("dedup_words {}", self.dedup_words),
+            format!("focus_mode {}", self.focus_mode),
+            format!("keystroke_log {}", self.keystroke_log),
+            format!("music_track {}", self.music_track),
+            format!("adaptive_spawn {}", self.adaptive_spawn),
+            format!("passage {}", self.passage),
+            format!("speed_ramp {}", self.speed_ramp),
+            format!("fullscreen {}", self.fullscreen),
+            format!("max_word_len {}", self.max_word_len),
+            format!("max_chars_per_frame {}", self.max_chars_per_frame),
+            format!("breathing_background {}", self.breathing_background),
+            format!("shrinking_words {}", self.shrinking_words),
+            format!("spawn_retries {}", self.spawn_retries),
+            format!("clear_input_on_escape {}", self.clear_input_on_escape),
+            format!("double_speed {}", self.double_speed),
+            format!("show_cash_rate {}", self.show_cash_rate),
+            format!("avoid_repeats {}", self.avoid_repeats),
+            format!("escalating_prices {}", self.escalating_prices),
+            format!("show_length_histogram {}", self.show_length_histogram),
+            format!("timed_slow_spawn {}", self.timed_slow_spawn),
+            format!("safe_start {}", self.safe_start),
+            format!("last_stand {}", self.last_stand),
+            format!("miss_penalty {}", self.miss_penalty),
+            format!("show_minimap {}", self.show_minimap),
+            format!("practice_weak_words {}", self.practice_weak_words),
+            format!("typed_log {}", self.typed_log),
+            format!("max_visible_words {}", self.max_visible_words),
+            format!("waves {}", self.waves),
+            format!("show_cash_delta {}", self.show_cash_delta),
+            format!("strict_submit {}", self.strict_submit),
+            format!("first_words_grace {}", self.first_words_grace),
+            format!("resume_session {}", self.resume_session),
+            format!("panic_min_cash {}", self.panic_min_cash),
+            format!("show_escape_times {}", self.show_escape_times),
+            format!("auto_difficulty {}", self.auto_difficulty),
+            format!("speedrun_hud {}", self.speedrun_hud),
+            format!("spawn_lanes {}", self.spawn_lanes)
+        ]
+    }
+}
+
+fn parse_into<T: FromStr>(value: &str, field: &mut T) {
+    if let Ok(parsed) = value.parse::<T>() {
+        *field = parsed;
+    }
+}
+
+// a key that can be typed into the words would take that character away from the words and the names
+fn parse_key_into(value: &str, field: &mut char) {
+    if let Ok(key) = value.parse::<char>() {
+        if !Word::is_typeable(key) {
+            *field = key;
+        }
+    }
+}
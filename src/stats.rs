@@ -0,0 +1,364 @@
+use serde::{ Deserialize, Serialize };
+
+use std::collections::VecDeque;
+
+use crate::difficulty::Difficulty;
+use crate::draw_helper;
+use crate::power_up::PurchaseCounts;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Typed,
+    Escaped,
+    Removed,
+    // escaped into an active shield
+    Shielded
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordOutcome {
+    pub word: String,
+    pub outcome: Outcome
+}
+
+// summary of a finished run, exported as json for external tooling
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunReport {
+    pub words_typed: u32,
+    pub wpm: f32,
+    pub accuracy: f32,
+    pub elapsed: f32,
+    pub difficulty: Difficulty,
+    pub best_streak: u32,
+    pub purchases: PurchaseCounts,
+    pub words: Vec<WordOutcome>
+}
+
+pub const HISTOGRAM_MAX_LEN: usize = 12;
+
+// typed words by their length in characters, the longest ones share the last bucket
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LengthHistogram {
+    counts: [u32; HISTOGRAM_MAX_LEN]
+}
+
+impl LengthHistogram {
+    pub fn record(&mut self, length: usize) {
+        if length == 0 {
+            return;
+        }
+
+        self.counts[length.min(HISTOGRAM_MAX_LEN) - 1] += 1;
+    }
+
+    // bucket `i` counts the words of `i + 1` characters
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    pub fn max_count(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+}
+
+// longest word of every accuracy bucket but the last one, which takes the rest
+pub const ACCURACY_BUCKET_MAX_LENS: [usize; 3] = [4, 7, 10];
+pub const ACCURACY_BUCKETS: usize = ACCURACY_BUCKET_MAX_LENS.len() + 1;
+
+// spawned, typed and escaped words by their length, to tell whether the long words get away more
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LengthAccuracy {
+    spawned: [u32; ACCURACY_BUCKETS],
+    typed: [u32; ACCURACY_BUCKETS],
+    escaped: [u32; ACCURACY_BUCKETS]
+}
+
+impl LengthAccuracy {
+    pub fn bucket(length: usize) -> usize {
+        ACCURACY_BUCKET_MAX_LENS.iter().position(|max_len| length <= *max_len).unwrap_or(ACCURACY_BUCKETS - 1)
+    }
+
+    // "1-4", "5-7", "8-10" and "11+"
+    pub fn bucket_label(bucket: usize) -> String {
+        let min_len = if bucket == 0 { 1 } else { ACCURACY_BUCKET_MAX_LENS[bucket - 1] + 1 };
+        match ACCURACY_BUCKET_MAX_LENS.get(bucket) {
+            Some(max_len) => format!("{}-{}", min_len, max_len),
+            None => format!("{}+", min_len)
+        }
+    }
+
+    pub fn record_spawned(&mut self, length: usize) {
+        self.spawned[LengthAccuracy::bucket(length)] += 1;
+    }
+
+    pub fn record_typed(&mut self, length: usize) {
+        self.typed[LengthAccuracy::bucket(length)] += 1;
+    }
+
+    pub fn record_escaped(&mut self, length: usize) {
+        self.escaped[LengthAccuracy::bucket(length)] += 1;
+    }
+
+    pub fn spawned(&self, bucket: usize) -> u32 {
+        self.spawned[bucket]
+    }
+
+    pub fn typed(&self, bucket: usize) -> u32 {
+        self.typed[bucket]
+    }
+
+    pub fn escaped(&self, bucket: usize) -> u32 {
+        self.escaped[bucket]
+    }
+
+    // share of the typed words among the typed and escaped ones, none before any of them
+    pub fn accuracy(&self, bucket: usize) -> Option<f32> {
+        let finished = self.typed[bucket] + self.escaped[bucket];
+        if finished == 0 {
+            return None;
+        }
+
+        Some(self.typed[bucket] as f32 / finished as f32)
+    }
+
+    // one "<lengths> <typed>/<spawned> (<accuracy>%)" entry per bucket
+    pub fn summary(&self) -> String {
+        (0 .. ACCURACY_BUCKETS).map(|bucket| {
+            let accuracy = self.accuracy(bucket).map(|accuracy| format!("{:.0}%", accuracy * 100.0)).unwrap_or_else(|| String::from("-"));
+            format!("{} {}/{} ({})", LengthAccuracy::bucket_label(bucket), self.typed[bucket], self.spawned[bucket], accuracy)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+    }
+}
+
+// everything that happened during the current run
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    pub typed_words: u32,
+    pub escaped_words: u32,
+    pub typed_chars: usize,
+    // words typed since the last escape
+    pub streak: u32,
+    pub best_streak: u32,
+    pub elapsed: f32,
+    pub difficulty: Difficulty,
+    // rewards and combo bonuses, refunds and starting cash don't count
+    pub total_cash_earned: f32,
+    pub typed_lengths: LengthHistogram,
+    pub length_accuracy: LengthAccuracy,
+    outcomes: Vec<WordOutcome>
+}
+
+impl RunStats {
+    pub fn new(difficulty: Difficulty) -> Self {
+        RunStats {
+            difficulty,
+            ..RunStats::default()
+        }
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.elapsed += seconds;
+    }
+
+    pub fn record_cash(&mut self, cash: f32) {
+        self.total_cash_earned += cash;
+    }
+
+    pub fn record_spawned(&mut self, word: &str) {
+        self.length_accuracy.record_spawned(word.chars().count());
+    }
+
+    pub fn record_typed(&mut self, word: &str) {
+        self.typed_words += 1;
+        self.typed_chars += word.chars().count();
+        self.typed_lengths.record(word.chars().count());
+        self.length_accuracy.record_typed(word.chars().count());
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.record(word, Outcome::Typed);
+    }
+
+    pub fn record_escaped(&mut self, word: &str) {
+        self.escaped_words += 1;
+        self.streak = 0;
+        self.length_accuracy.record_escaped(word.chars().count());
+        self.record(word, Outcome::Escaped);
+    }
+
+    // the shield took the hit, so neither the accuracy nor the streak suffer
+    pub fn record_shielded(&mut self, word: &str) {
+        self.record(word, Outcome::Shielded);
+    }
+
+    pub fn escaped_labels(&self) -> impl Iterator<Item = &str> {
+        self.outcomes.iter().filter(|outcome| outcome.outcome == Outcome::Escaped).map(|outcome| outcome.word.as_str())
+    }
+
+    pub fn record_removed(&mut self, word: &str) {
+        self.record(word, Outcome::Removed);
+    }
+
+    // drops the latest removal of the word, when its purchase is undone
+    pub fn forget_removed(&mut self, word: &str) {
+        let removed = self.outcomes.iter().rposition(|outcome| outcome.word == word && outcome.outcome == Outcome::Removed);
+        if let Some(index) = removed {
+            self.outcomes.remove(index);
+        }
+    }
+
+    pub fn outcomes(&self) -> &[WordOutcome] {
+        &self.outcomes
+    }
+
+    // standard 5 characters per word
+    pub fn wpm(&self) -> f32 {
+        if self.elapsed <= 0.0 {
+            return 0.0;
+        }
+
+        (self.typed_chars as f32 / 5.0) / (self.elapsed / 60.0)
+    }
+
+    pub fn cash_per_minute(&self) -> f32 {
+        if self.elapsed <= 0.0 {
+            return 0.0;
+        }
+
+        self.total_cash_earned / (self.elapsed / 60.0)
+    }
+
+    // share of the words that reached the player and were typed before escaping
+    pub fn accuracy(&self) -> f32 {
+        let attempted = self.typed_words + self.escaped_words;
+        if attempted == 0 {
+            return 0.0;
+        }
+
+        self.typed_words as f32 / attempted as f32
+    }
+
+    // the lines of the speedrun hud, one stat per line
+    pub fn speedrun_summary(&self, remaining_lifes: u32) -> String {
+        format!("Time {}\nWPM {:.1}\nAccuracy {:.0}%\nWords {}\nLifes {}",
+                draw_helper::format_elapsed(self.elapsed), self.wpm(), self.accuracy() * 100.0, self.typed_words, remaining_lifes)
+    }
+
+    // the purchases are kept by the game, they aren't part of the typing stats
+    pub fn report(&self, purchases: PurchaseCounts) -> RunReport {
+        RunReport {
+            words_typed: self.typed_words,
+            wpm: self.wpm(),
+            accuracy: self.accuracy(),
+            elapsed: self.elapsed,
+            difficulty: self.difficulty,
+            best_streak: self.best_streak,
+            purchases,
+            words: self.outcomes.clone()
+        }
+    }
+
+    fn record(&mut self, word: &str, outcome: Outcome) {
+        self.outcomes.push(WordOutcome { word: String::from(word), outcome });
+    }
+}
+
+// keystrokes that happened during the last `window` seconds
+pub fn keystrokes_in_window(timestamps: &VecDeque<f32>, now: f32, window: f32) -> usize {
+    timestamps.iter().filter(|time| now - **time <= window && **time <= now).count()
+}
+
+// newest word first, the oldest ones fall off past `max_len`
+pub fn push_typed_log(log: &mut VecDeque<String>, word: &str, max_len: usize) {
+    log.push_front(String::from(word));
+    log.truncate(max_len);
+}
+
+// fires once when the count of typed words lands on a multiple of the interval
+pub fn is_milestone(typed_words: u32, interval: u32) -> bool {
+    interval > 0 && typed_words > 0 && typed_words.is_multiple_of(interval)
+}
+
+// logged for the backspace key, which has no printable character
+pub const BACKSPACE: char = '\u{8}';
+
+// logged for an (Enter) that submitted input matching no word
+pub const SUBMIT: char = '\n';
+
+// one "seconds,key" row per keystroke, under a header
+pub fn format_keystroke_csv(keystrokes: &[(f32, char)]) -> String {
+    let mut csv = String::from("time,key\n");
+    for (time, key) in keystrokes {
+        let key = match *key {
+            BACKSPACE => String::from("backspace"),
+            SUBMIT => String::from("enter"),
+            key => key.to_string()
+        };
+        csv += &format!("{:.3},{}\n", time, key);
+    }
+
+    csv
+}
+
+// double speed runs stand out on the scoreboard
+pub fn leaderboard_name(username: &str, double_speed: bool) -> String {
+    if double_speed {
+        format!("{}[x{}]", username, crate::difficulty::CHALLENGE_SPEED_FACTOR)
+    }
+    else {
+        String::from(username)
+    }
+}
+
+// nobody types several characters within a single frame, a paste delivers them all at once
+pub fn is_input_burst(chars_in_frame: usize, max_chars_per_frame: usize) -> bool {
+    max_chars_per_frame > 0 && chars_in_frame > max_chars_per_frame
+}
+
+pub const STREAK_PITCH_STEP: f32 = 0.04;
+pub const MAX_STREAK_PITCH: f32 = 1.5;
+
+// the typed word sound climbs a little with every word of the streak, up to a cap
+pub fn streak_pitch(streak: u32) -> f32 {
+    (1.0 + STREAK_PITCH_STEP * streak.saturating_sub(1) as f32).min(MAX_STREAK_PITCH)
+}
+
+// least typed words for every message, from the best runs down
+fn flavor_table(difficulty: Difficulty) -> [(u32, &'static str); 4] {
+    let [top, good, fair] = match difficulty {
+        Difficulty::Easy => [80, 30, 10],
+        Difficulty::Normal => [50, 20, 5],
+        Difficulty::Hard => [30, 12, 3]
+    };
+
+    [
+        (top, "You're a madman, niiice :)"),
+        (good, "Amazing, but can you do better?"),
+        (fair, "Not very bad!"),
+        (0, "Bummer, I know you can do better :) Try again!")
+    ]
+}
+
+pub fn flavor_message(difficulty: Difficulty, typed_words: u32) -> &'static str {
+    flavor_table(difficulty).iter()
+        .find(|(least_words, _)| typed_words >= *least_words)
+        .map_or("", |(_, message)| message)
+}
+
+// rewards slow but steady runs, the thresholds are in seconds survived
+pub fn survival_message(elapsed: f32) -> &'static str {
+    if elapsed < 60.0 {
+        "That was quick, hang in there a little longer!"
+    }
+    else if elapsed < 180.0 {
+        "Steady hands, keep it up!"
+    }
+    else if elapsed < 300.0 {
+        "What an endurance, impressive!"
+    }
+    else {
+        "Unbreakable, the words just can't get you :)"
+    }
+}
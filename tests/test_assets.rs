@@ -0,0 +1,34 @@
+use ggez::GameError;
+use ggez::graphics::Font;
+
+use type_racer::assets::{ Assets, Sound };
+
+use std::path::PathBuf;
+
+#[test]
+fn missing_font_falls_back_to_default() {
+    let missing = Err(GameError::ResourceNotFound(String::from("/RedHatDisplay-Regular.otf"), Vec::new()));
+
+    assert_eq!(Assets::font_or_default(missing), Font::default());
+}
+
+#[test]
+fn music_tracks_are_the_sorted_music_files() {
+    let paths = ["/words.dict", "/game-background-music.wav", "/collect-point.wav", "/calm-background-music.wav", "/game-background-music.wav"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    assert_eq!(Assets::music_tracks_from(paths), vec!["/calm-background-music.wav", "/game-background-music.wav"]);
+}
+
+
+#[test]
+fn silent_sound_takes_the_settings_without_a_device() {
+    let mut sound = Sound::silent();
+    sound.set_volume(0.5);
+    sound.set_pitch(1.2);
+    sound.set_repeat(true);
+
+    assert!(sound.is_silent());
+}
\ No newline at end of file
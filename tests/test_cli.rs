@@ -0,0 +1,24 @@
+use type_racer::cli::*;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn window_size_defaults_without_flags() {
+    assert_eq!(parse_window_size(&[]), Ok(DEFAULT_WINDOW_SIZE));
+}
+
+#[test]
+fn window_size_from_flags() {
+    assert_eq!(parse_window_size(&args("--width 1600 --height 900")), Ok((1600.0, 900.0)));
+    assert_eq!(parse_window_size(&args("--height 900")), Ok((DEFAULT_WINDOW_SIZE.0, 900.0)));
+}
+
+#[test]
+fn window_size_rejects_bad_flags() {
+    assert!(parse_window_size(&args("--width")).is_err());
+    assert!(parse_window_size(&args("--width wide")).is_err());
+    assert!(parse_window_size(&args("--depth 3")).is_err());
+    assert!(parse_window_size(&args("--width 320 --height 240")).is_err());
+}
@@ -0,0 +1,26 @@
+use type_racer::debug::FrameTimes;
+
+#[test]
+fn frame_times_report_once_per_window() {
+    let mut frame_times = FrameTimes::new(3);
+
+    assert!(!frame_times.record(0.016));
+    assert!(!frame_times.record(0.016));
+    assert!(frame_times.record(0.016));
+    assert!(!frame_times.record(0.016));
+}
+
+#[test]
+fn frame_times_summary_covers_the_latest_window() {
+    let mut frame_times = FrameTimes::new(3);
+    assert_eq!(frame_times.summary(), None);
+
+    for seconds in [0.1, 0.01, 0.02, 0.03] {
+        frame_times.record(seconds);
+    }
+
+    let (min, max, avg) = frame_times.summary().unwrap();
+    assert_eq!(min, 0.01);
+    assert_eq!(max, 0.03);
+    assert!((avg - 0.02).abs() < 1e-6);
+}
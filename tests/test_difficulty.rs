@@ -0,0 +1,294 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use type_racer::difficulty::*;
+use type_racer::settings::Settings;
+
+#[test]
+fn word_speed_ranges_are_valid() {
+    for difficulty in Difficulty::ALL.iter() {
+        let (min_speed, max_speed) = difficulty.word_speed_range();
+
+        assert!(0.0 < min_speed && min_speed < max_speed, "{}", difficulty);
+    }
+}
+
+#[test]
+fn random_word_speed_stays_in_range() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    for difficulty in Difficulty::ALL.iter() {
+        let (min_speed, max_speed) = difficulty.word_speed_range();
+
+        for _ in 0 .. 100 {
+            let speed = difficulty.random_word_speed(&mut rng);
+            assert!(min_speed <= speed && speed < max_speed);
+        }
+    }
+}
+
+#[test]
+fn difficulty_parses_its_name() {
+    for difficulty in Difficulty::ALL.iter() {
+        assert_eq!(difficulty.to_string().parse::<Difficulty>(), Ok(*difficulty));
+    }
+
+    assert!("Impossible".parse::<Difficulty>().is_err());
+}
+
+#[test]
+fn difficulty_next_cycles_through_all() {
+    assert_eq!(Difficulty::Easy.next(), Difficulty::Normal);
+    assert_eq!(Difficulty::Normal.next(), Difficulty::Hard);
+    assert_eq!(Difficulty::Hard.next(), Difficulty::Easy);
+}
+
+
+#[test]
+fn color_changing_chances_are_percentages() {
+    for difficulty in Difficulty::ALL.iter() {
+        assert!(difficulty.color_changing_chance() <= 100);
+    }
+}
+
+#[test]
+fn roll_chance_at_the_bounds() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    assert!((0 .. 1000).all(|_| !roll_chance(&mut rng, 0)));
+    assert!((0 .. 1000).all(|_| roll_chance(&mut rng, 100)));
+}
+#[test]
+fn spawn_gap_scale_follows_wpm() {
+    assert_eq!(spawn_gap_scale(ADAPTIVE_BASELINE_WPM), 1.0);
+    assert!(spawn_gap_scale(60.0) < 1.0);
+    assert!(spawn_gap_scale(30.0) > 1.0);
+}
+
+#[test]
+fn spawn_gap_scale_is_clamped() {
+    assert_eq!(spawn_gap_scale(0.0), MAX_SPAWN_GAP_SCALE);
+    assert_eq!(spawn_gap_scale(1.0), MAX_SPAWN_GAP_SCALE);
+    assert_eq!(spawn_gap_scale(500.0), MIN_SPAWN_GAP_SCALE);
+}
+
+#[test]
+fn spawn_timing_does_not_drift_without_the_ramp() {
+    let mut speed_up = 0.0;
+    for _ in 0 .. 100 {
+        speed_up = next_speed_up(speed_up, false);
+    }
+
+    assert_eq!(spawn_gap_range(speed_up), spawn_gap_range(0.0));
+}
+
+#[test]
+fn spawn_timing_shrinks_with_the_ramp() {
+    let speed_up = next_speed_up(next_speed_up(0.0, true), true);
+    let (min_gap, max_gap) = spawn_gap_range(speed_up);
+
+    assert!(min_gap < 3.0 && max_gap < 3.5);
+}
+
+
+#[test]
+fn speed_factor_only_applies_with_double_speed() {
+    assert_eq!(speed_factor(false), 1.0);
+    assert_eq!(speed_factor(true), CHALLENGE_SPEED_FACTOR);
+}
+
+#[test]
+fn scaled_spawn_gap_range_stays_valid() {
+    for step in 0 .. 200 {
+        let speed_up = step as f32 * SPAWN_SPEED_UP_STEP;
+        let (min_gap, max_gap) = scaled_spawn_gap_range(speed_up, CHALLENGE_SPEED_FACTOR);
+        assert!(min_gap < max_gap);
+    }
+}
+
+#[test]
+fn scaled_spawn_gap_range_spawns_faster() {
+    let (min_gap, max_gap) = spawn_gap_range(0.0);
+
+    assert_eq!(scaled_spawn_gap_range(0.0, 1.5), (min_gap / 1.5, max_gap / 1.5));
+    assert_eq!(scaled_spawn_gap_range(0.0, 1.0), (min_gap, max_gap));
+}
+
+#[test]
+fn pick_word_never_repeats_the_excluded_word() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let pool = vec![String::from("one"), String::from("two"), String::from("three")];
+
+    let mut last = pick_word(&mut rng, &pool, None);
+    for _ in 0 .. 1000 {
+        let next = pick_word(&mut rng, &pool, last.as_deref());
+        assert_ne!(next, last);
+        last = next;
+    }
+}
+
+#[test]
+fn pick_word_skips_repeated_lines_of_the_excluded_word() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let pool = vec![String::from("same"), String::from("same"), String::from("same"), String::from("other")];
+
+    for _ in 0 .. 100 {
+        assert_eq!(pick_word(&mut rng, &pool, Some("same")), Some(String::from("other")));
+    }
+}
+
+#[test]
+fn pick_word_repeats_a_single_word_pool() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let pool = vec![String::from("only")];
+
+    assert_eq!(pick_word(&mut rng, &pool, Some("only")), Some(String::from("only")));
+}
+
+#[test]
+fn pick_word_from_an_empty_pool() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    assert_eq!(pick_word(&mut rng, &[], None), None);
+}
+
+#[test]
+fn last_stand_holds_the_spawn_at_one_life() {
+    assert!(holds_spawn(true, 1, 3));
+}
+
+#[test]
+fn last_stand_lets_the_word_spawn_on_a_clear_board() {
+    assert!(!holds_spawn(true, 1, 0));
+}
+
+#[test]
+fn spawns_go_on_without_last_stand_or_with_more_lifes() {
+    assert!(!holds_spawn(false, 1, 3));
+    assert!(!holds_spawn(true, 2, 3));
+}
+
+#[test]
+fn spawning_halts_at_the_max_visible_words() {
+    assert!(board_full(4, 4));
+    assert!(board_full(5, 4));
+}
+
+#[test]
+fn spawning_resumes_once_a_word_clears() {
+    assert!(!board_full(3, 4));
+}
+
+#[test]
+fn no_max_visible_words_never_fills_the_board() {
+    assert!(!board_full(100, 0));
+}
+
+#[test]
+fn waves_grow_longer() {
+    assert_eq!(wave_size(1), FIRST_WAVE_SIZE);
+    assert_eq!(wave_size(3), FIRST_WAVE_SIZE + 2 * WAVE_SIZE_STEP);
+    assert_eq!(wave_size(0), FIRST_WAVE_SIZE);
+}
+
+#[test]
+fn losing_a_life_keeps_the_run_going_while_lifes_are_left() {
+    let mut lifes = 3;
+
+    assert!(!lose_life(&mut lifes));
+    assert_eq!(lifes, 2);
+}
+
+#[test]
+fn losing_the_last_life_ends_the_run() {
+    let mut lifes = 1;
+
+    assert!(lose_life(&mut lifes));
+    assert_eq!(lifes, 0);
+    assert!(lose_life(&mut lifes));
+}
+
+#[test]
+fn strict_submit_at_the_last_life_ends_the_run() {
+    let settings = Settings { strict_submit: true, ..Settings::default() };
+
+    assert_eq!(failed_submit(&settings, 1, false), Some(FailedSubmit { penalty: 0.0, loses_life: true, ends_run: true }));
+    assert_eq!(failed_submit(&settings, 3, false), Some(FailedSubmit { penalty: 0.0, loses_life: true, ends_run: false }));
+}
+
+#[test]
+fn failed_submit_without_strict_mode_only_takes_the_penalty() {
+    let settings = Settings { miss_penalty: 25.0, ..Settings::default() };
+
+    assert_eq!(failed_submit(&settings, 1, false), Some(FailedSubmit { penalty: 25.0, loses_life: false, ends_run: false }));
+}
+
+#[test]
+fn failed_submit_is_ignored_with_both_settings_off() {
+    assert_eq!(failed_submit(&Settings::default(), 1, false), None);
+}
+
+#[test]
+fn first_grace_words_have_a_capped_speed() {
+    for difficulty in Difficulty::ALL {
+        let (min_speed, max_speed) = difficulty.word_speed_range();
+
+        assert_eq!(difficulty.grace_speed_cap(0), Some(min_speed * difficulty.grace_speed_factor()));
+        for spawned in 0 .. difficulty.grace_words() {
+            let cap = difficulty.grace_speed_cap(spawned).unwrap();
+            assert!(cap < max_speed);
+        }
+    }
+}
+
+#[test]
+fn grace_cap_climbs_word_by_word() {
+    let difficulty = Difficulty::Easy;
+
+    assert!(difficulty.grace_speed_cap(1).unwrap() > difficulty.grace_speed_cap(0).unwrap());
+}
+
+#[test]
+fn words_after_the_grace_are_not_capped() {
+    for difficulty in Difficulty::ALL {
+        assert_eq!(difficulty.grace_speed_cap(difficulty.grace_words()), None);
+        assert_eq!(difficulty.grace_speed_cap(100), None);
+    }
+}
+
+#[test]
+fn auto_tuning_without_history_is_normal() {
+    let tuning = auto_tuning(&[], 5);
+
+    assert_eq!(tuning, AutoTuning { difficulty: Difficulty::Normal, starting_lifes: 5, spawn_rate: 1.0 });
+}
+
+#[test]
+fn auto_tuning_follows_the_median_score() {
+    let beginner = auto_tuning(&[120.0, 80.0, 2000.0], 5);
+    assert_eq!(beginner.difficulty, Difficulty::Easy);
+    assert_eq!(beginner.starting_lifes, 6);
+    assert!(beginner.spawn_rate < 1.0);
+
+    assert_eq!(auto_tuning(&[900.0, 1100.0], 5).difficulty, Difficulty::Normal);
+
+    let skilled = auto_tuning(&[1400.0, 1800.0, 2500.0, 9000.0], 5);
+    assert_eq!(skilled, AutoTuning { difficulty: Difficulty::Hard, starting_lifes: 5, spawn_rate: 1.0 });
+
+    let expert = auto_tuning(&[4200.0, 5000.0, 6100.0], 5);
+    assert_eq!(expert.difficulty, Difficulty::Hard);
+    assert_eq!(expert.starting_lifes, 4);
+    assert!(expert.spawn_rate > 1.0);
+}
+
+#[test]
+fn auto_tuning_keeps_at_least_one_life() {
+    assert_eq!(auto_tuning(&[AUTO_EXPERT_FROM], 1).starting_lifes, 1);
+}
+
+#[test]
+fn strict_submit_of_a_word_on_screen_is_no_miss() {
+    let settings = Settings { strict_submit: true, miss_penalty: 25.0, ..Settings::default() };
+
+    assert_eq!(failed_submit(&settings, 1, true), None);
+}
\ No newline at end of file
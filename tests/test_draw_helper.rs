@@ -0,0 +1,176 @@
+use ggez::graphics::{ Color, Rect };
+use ggez::mint::Vector2;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use type_racer::draw_helper::*;
+
+#[test]
+fn format_elapsed_as_minutes_and_seconds() {
+    assert_eq!(format_elapsed(0.0), "0:00");
+    assert_eq!(format_elapsed(65.7), "1:05");
+    assert_eq!(format_elapsed(-3.0), "0:00");
+}
+
+#[test]
+fn format_scoreboard_numbers_the_lines() {
+    let scoreboard = vec![String::from("george 12.00"), String::from("ivan 3.50")];
+
+    assert_eq!(format_scoreboard(&scoreboard), "1) george 12.00\n2) ivan 3.50\n");
+}
+
+#[test]
+fn spawn_band_clears_the_hud() {
+    assert_eq!(spawn_band(1000.0, 40.0, 50.0, 30.0, 10.0), (50.0, 910.0));
+}
+
+#[test]
+fn spawn_band_stays_valid_on_tiny_screens() {
+    let (min_y, max_y) = spawn_band(60.0, 40.0, 50.0, 30.0, 10.0);
+
+    assert!(min_y < max_y);
+}
+
+
+#[test]
+fn breathing_level_peaks_halfway_through_the_period() {
+    assert!(breathing_level(0.0, 4.0, 0.12).abs() < 1e-6);
+    assert!((breathing_level(2.0, 4.0, 0.12) - 0.12).abs() < 1e-6);
+    assert!(breathing_level(4.0, 4.0, 0.12).abs() < 1e-6);
+}
+
+#[test]
+fn breathing_level_stays_in_range() {
+    for step in 0 .. 100 {
+        let level = breathing_level(step as f32 * 0.13, 4.0, 0.12);
+        assert!((0.0 ..= 0.12 + 1e-6).contains(&level));
+    }
+}
+
+#[test]
+fn spawn_point_on_an_empty_board() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let point = find_spawn_point(&mut rng, (50.0, 900.0), Vector2 { x: 80.0, y: 30.0 }, &[], 10).unwrap();
+
+    assert_eq!(point.x, 0.0);
+    assert!((50.0 .. 900.0).contains(&point.y));
+}
+
+#[test]
+fn spawn_point_avoids_the_occupied_rects() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let occupied = [Rect::new(0.0, 0.0, 100.0, 500.0)];
+
+    for _ in 0 .. 50 {
+        let point = find_spawn_point(&mut rng, (0.0, 900.0), Vector2 { x: 80.0, y: 30.0 }, &occupied, 100).unwrap();
+        assert!(point.y >= 500.0);
+    }
+}
+
+#[test]
+fn spawn_point_gives_up_on_a_full_board() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let occupied = [Rect::new(0.0, 0.0, 100.0, 1000.0)];
+
+    assert_eq!(find_spawn_point(&mut rng, (50.0, 900.0), Vector2 { x: 80.0, y: 30.0 }, &occupied, 10), None);
+}
+
+#[test]
+fn spawn_point_without_tries() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    assert_eq!(find_spawn_point(&mut rng, (50.0, 900.0), Vector2 { x: 80.0, y: 30.0 }, &[], 0), None);
+}
+
+#[test]
+fn fitting_text_keeps_its_size() {
+    assert_eq!(fit_scale(300.0, 600.0), 1.0);
+    assert_eq!(fit_scale(600.0, 600.0), 1.0);
+}
+
+#[test]
+fn oversized_text_is_scaled_to_fit() {
+    let scale = fit_scale(1500.0, 600.0);
+
+    assert_eq!(scale, 0.4);
+    assert!(1500.0 * scale <= 600.0);
+}
+
+#[test]
+fn empty_text_keeps_its_size() {
+    assert_eq!(fit_scale(0.0, 600.0), 1.0);
+}
+
+#[test]
+fn urgency_goes_from_green_to_red() {
+    assert_eq!(urgency_color(0.0), Color::new(0.0, 1.0, 0.0, 1.0));
+    assert_eq!(urgency_color(0.5), Color::new(0.5, 0.5, 0.0, 1.0));
+    assert_eq!(urgency_color(1.0), Color::new(1.0, 0.0, 0.0, 1.0));
+}
+
+#[test]
+fn urgency_is_clamped_to_the_screen() {
+    assert_eq!(urgency_color(-0.5), urgency_color(0.0));
+    assert_eq!(urgency_color(1.5), urgency_color(1.0));
+}
+
+#[test]
+fn modifier_badge_names_the_active_modifiers() {
+    assert_eq!(modifier_badge(true, false), Some("SHIFT"));
+    assert_eq!(modifier_badge(false, true), Some("CAPS"));
+    assert_eq!(modifier_badge(true, true), Some("SHIFT CAPS"));
+}
+
+#[test]
+fn no_modifier_badge_for_lower_case_input() {
+    assert_eq!(modifier_badge(false, false), None);
+}
+
+#[test]
+fn lane_tops_are_evenly_spaced_across_the_band() {
+    assert_eq!(lane_tops((100.0, 500.0), 30.0, 5), vec![100.0, 200.0, 300.0, 400.0, 500.0]);
+    assert_eq!(lane_tops((100.0, 500.0), 30.0, 1), vec![300.0]);
+}
+
+#[test]
+fn lane_tops_keep_a_word_height_apart() {
+    let tops = lane_tops((100.0, 190.0), 30.0, 5);
+
+    assert_eq!(tops, vec![100.0, 130.0, 160.0, 190.0]);
+    assert_eq!(lane_tops((100.0, 110.0), 30.0, 5).len(), 1);
+}
+
+#[test]
+fn lane_mode_skips_the_occupied_lanes() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let tops = lane_tops((100.0, 500.0), 30.0, 5);
+    let size = Vector2 { x: 80.0, y: 30.0 };
+    // words still near the left edge in every lane but the one at 300
+    let occupied = [100.0, 200.0, 400.0, 500.0].iter().map(|top| Rect::new(20.0, *top, 80.0, 30.0)).collect::<Vec<Rect>>();
+
+    for _ in 0 .. 50 {
+        assert_eq!(pick_lane(&mut rng, &tops, size, &occupied), Some(300.0));
+    }
+}
+
+#[test]
+fn lane_is_free_again_once_its_word_moved_on() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let size = Vector2 { x: 80.0, y: 30.0 };
+    let occupied = [Rect::new(400.0, 100.0, 80.0, 30.0)];
+
+    assert_eq!(pick_lane(&mut rng, &[100.0], size, &occupied), Some(100.0));
+    let taken = [Rect::new(0.0, 100.0, 80.0, 30.0)];
+    assert_eq!(pick_lane(&mut rng, &[100.0], size, &taken), None);
+}
+
+#[test]
+fn rect_batch_gathers_the_rects_of_a_frame() {
+    let mut batch = RectBatch::new();
+    assert!(batch.is_empty());
+
+    batch.add(Rect::new(0.0, 0.0, 10.0, 3.0), Color::WHITE);
+    batch.add(Rect::new(0.0, 20.0, 10.0, 3.0), Color::WHITE);
+
+    assert_eq!(batch.len(), 2);
+}
\ No newline at end of file
@@ -0,0 +1,184 @@
+use type_racer::difficulty::CHALLENGE_SPEED_FACTOR;
+use type_racer::economy::{ CashDelta, Economy, Wallet, MAX_PANIC_INVULNERABILITY };
+
+#[test]
+fn typed_words_earn_expected_cash() {
+    let economy = Economy {
+        buy_life_tax: 100.0,
+        remove_words_tax: 200.0,
+        slow_word_spawn_tax: 400.0,
+        shield_tax: 300.0,
+        magnet_tax: 250.0,
+        color_changing_multiplier: 3.0,
+        cash_rate: 0.5,
+        combo_window: 1.0,
+        combo_bonus_step: 5.0,
+        wave_bonus_step: 50.0,
+        panic_cash_per_second: 100.0
+    };
+
+    // (speed, label, color-changing)
+    let typed_words = [(100.0, "rust", false), (200.0, "ggez", true), (50.0, "type", false)];
+    let cash: f32 = typed_words.iter()
+        .map(|(speed, label, is_color_changing)| economy.cash_for(economy.reward(*speed, label, *is_color_changing)))
+        .sum();
+
+    // rewards are 4.0, 24.0 and 2.0, half of them is paid out
+    assert_eq!(cash, 15.0);
+}
+
+#[test]
+fn starting_cash_presets_follow_the_taxes() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.starting_cash_presets(), [0.0, 300.0, 350.0, 1000.0]);
+}
+
+#[test]
+fn combo_bonus_escalates_inside_the_window() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.combo_bonus(0.5, 1), economy.combo_bonus_step);
+    assert_eq!(economy.combo_bonus(0.5, 3), 3.0 * economy.combo_bonus_step);
+    assert_eq!(economy.combo_bonus(economy.combo_window, 2), 2.0 * economy.combo_bonus_step);
+}
+
+#[test]
+fn combo_bonus_is_zero_for_slow_words() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.combo_bonus(economy.combo_window + 0.1, 5), 0.0);
+    assert_eq!(economy.combo_bonus(f32::MAX, 0), 0.0);
+}
+
+
+#[test]
+fn reward_scales_with_the_speed_factor() {
+    let economy = Economy::default();
+    let reward = economy.reward(200.0, "hello", false);
+
+    assert_eq!(economy.reward(200.0 * CHALLENGE_SPEED_FACTOR, "hello", false), reward * CHALLENGE_SPEED_FACTOR);
+}
+
+#[test]
+fn next_buff_progress_towards_the_cheapest_unaffordable_buff() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.next_buff_progress(0.0), Some(0.0));
+    assert_eq!(economy.next_buff_progress(150.0), Some(150.0 / economy.buy_life_tax));
+    // the life is affordable, the words removal is next
+    assert_eq!(economy.next_buff_progress(320.0), Some(320.0 / economy.remove_words_tax));
+    assert_eq!(economy.next_buff_progress(400.0), Some(400.0 / economy.shield_tax));
+}
+
+#[test]
+fn next_buff_progress_once_everything_is_affordable() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.next_buff_progress(economy.slow_word_spawn_tax), None);
+}
+
+#[test]
+fn penalty_takes_cash() {
+    let mut wallet = Wallet::new(100.0);
+
+    assert_eq!(wallet.take(30.0), 30.0);
+    assert_eq!(wallet.cash(), 70.0);
+}
+
+#[test]
+fn penalty_never_takes_the_cash_below_zero() {
+    let mut wallet = Wallet::new(10.0);
+
+    assert_eq!(wallet.take(30.0), 10.0);
+    assert_eq!(wallet.cash(), 0.0);
+
+    assert_eq!(wallet.take(30.0), 0.0);
+    assert_eq!(wallet.cash(), 0.0);
+}
+
+#[test]
+fn later_waves_pay_a_bigger_bonus() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.wave_bonus(1), economy.wave_bonus_step);
+    assert!(economy.wave_bonus(4) > economy.wave_bonus(3));
+}
+
+#[test]
+fn cash_delta_shows_the_latest_change() {
+    let mut delta = CashDelta::default();
+    assert_eq!(delta.label(), None);
+
+    delta.record(30.0);
+    assert_eq!(delta.label().as_deref(), Some("+30.00"));
+
+    delta.update(CashDelta::DURATION);
+    assert_eq!(delta.label(), None);
+}
+
+#[test]
+fn quick_cash_changes_add_up() {
+    let mut delta = CashDelta::default();
+    delta.record(30.0);
+    delta.update(0.5);
+    delta.record(-300.0);
+
+    assert_eq!(delta.label().as_deref(), Some("-270.00"));
+    assert_eq!(delta.alpha(), 1.0);
+}
+
+#[test]
+fn faded_cash_delta_starts_over() {
+    let mut delta = CashDelta::default();
+    delta.record(30.0);
+    delta.update(CashDelta::DURATION);
+    delta.record(5.0);
+
+    assert_eq!(delta.amount(), 5.0);
+}
+
+#[test]
+fn wallet_spends_what_it_can_afford() {
+    let mut wallet = Wallet::new(100.0);
+
+    assert!(wallet.spend(60.0));
+    assert_eq!(wallet.cash(), 40.0);
+    assert_eq!(wallet.delta().label().as_deref(), Some("-60.00"));
+}
+
+#[test]
+fn wallet_refuses_what_it_can_not_afford() {
+    let mut wallet = Wallet::new(50.0);
+
+    assert!(!wallet.spend(60.0));
+    assert_eq!(wallet.cash(), 50.0);
+    assert_eq!(wallet.delta().label(), None);
+}
+
+#[test]
+fn wallet_penalty_saturates_at_zero() {
+    let mut wallet = Wallet::new(20.0);
+
+    assert_eq!(wallet.take(50.0), 20.0);
+    assert_eq!(wallet.cash(), 0.0);
+    assert_eq!(wallet.take(50.0), 0.0);
+}
+
+#[test]
+fn wallet_adds_earnings() {
+    let mut wallet = Wallet::new(0.0);
+    wallet.add(30.0);
+    wallet.add(-10.0);
+
+    assert_eq!(wallet.cash(), 30.0);
+}
+
+#[test]
+fn panic_invulnerability_grows_with_the_cash_spent() {
+    let economy = Economy::default();
+
+    assert_eq!(economy.panic_invulnerability(0.0), 0.0);
+    assert_eq!(economy.panic_invulnerability(250.0), 2.5);
+    assert_eq!(economy.panic_invulnerability(5000.0), MAX_PANIC_INVULNERABILITY);
+}
\ No newline at end of file
@@ -1,10 +1,11 @@
-use ggez::mint::Point2;
+use ggez::mint::{ Point2, Vector2 };
 use ggez::{ Context, GameResult };
 use ggez::graphics::Color;
 use quickcheck::quickcheck;
 
 use type_racer::entities::*;
 use type_racer::assets::Sprite;
+use type_racer::economy::Economy;
 
 #[derive(Debug)]
 struct MockSprite {
@@ -24,6 +25,9 @@ impl Sprite for MockSprite {
     fn height(&self, _ctx: &mut Context) -> f32 {
         self.height
     }
+
+    fn set_text(&mut self, _text: &str) {
+    }
 }
 
 quickcheck! {
@@ -31,8 +35,8 @@ quickcheck! {
         let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
         let mut word = Word::new("something", Point2 { x, y }, 10.0, mock_sprite, false).unwrap();
 
-        let old_pos = word.pos.clone();
-        word.update(10.0);
+        let old_pos = word.pos;
+        word.update(10.0, f32::MAX, 100.0);
 
         word.pos.x > old_pos.x && word.pos.y == old_pos.y
     }
@@ -45,7 +49,7 @@ quickcheck! {
         };
         let word = Word::new(&label, point, 10.0, mock_sprite, false).unwrap();
 
-        word.label() == &label
+        word.label() == label
     }
 
     fn word_translate(x: f32, y: f32) -> bool {
@@ -84,14 +88,15 @@ quickcheck! {
             x: 0.0,
             y: 0.0
         };
-        let mut word = Word::new(&label, point, speed, mock_sprite, color_changing).unwrap();
-        let reward = word.get_reward();
+        let word = Word::new(&label, point, speed, mock_sprite, color_changing).unwrap();
+        let reward = word.get_reward(&Economy::default());
         let color_multiplayer = {
             if color_changing {
-                2.0;
+                2.0
+            }
+            else {
+                1.0
             }
-
-            1.0
         };
 
         let expected_reward = speed * color_multiplayer * (label.len() as f32) / 100.0;
@@ -99,4 +104,390 @@ quickcheck! {
 
       (reward - expected_reward).abs() < f32::EPSILON
     }
+}
+
+#[test]
+fn word_default_colors() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let word = Word::new("test", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    assert_eq!(word.default_color(), Word::DEFAULT_COLOR);
+    assert_eq!(word.typed_color(), Word::TYPED_COLOR);
+}
+
+#[test]
+fn word_custom_colors() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let mut word = Word::new("test", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    let default_color = Color::from_rgb(10, 20, 30);
+    let typed_color = Color::from_rgb(40, 50, 60);
+
+    word.set_default_color(default_color);
+    word.set_typed_color(typed_color);
+
+    assert_eq!(word.default_color(), default_color);
+    assert_eq!(word.typed_color(), typed_color);
+}
+
+#[test]
+fn word_escapes_when_right_edge_reaches_deadline() {
+    let width = 100.0;
+    let deadline = 1190.0;
+    let mock_sprite = Box::new(MockSprite { width, height: 30.0 });
+    let mut word = Word::new("test", Point2 { x: 1089.0, y: 0.0 }, 1.0, mock_sprite, false).unwrap();
+
+    assert!(!word.has_escaped(width, deadline));
+
+    word.update(1.0, f32::MAX, width);
+    assert!(word.has_escaped(width, deadline));
+}
+
+#[test]
+fn word_update_signals_escape_once() {
+    let width = 100.0;
+    let screen_width = 1200.0;
+    let start_x = screen_width - Word::ESCAPE_MARGIN - width - 5.0;
+    let mock_sprite = Box::new(MockSprite { width, height: 30.0 });
+    let mut word = Word::new("test", Point2 { x: start_x, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    assert!(!word.update(0.25, screen_width, width));
+    assert!(word.update(0.25, screen_width, width));
+    assert!(!word.update(0.25, screen_width, width));
+}
+#[test]
+fn rtl_word_matches_typed_input() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let word = Word::new("שלום", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    let mut input = String::new();
+    for letter in "שלום".chars() {
+        assert!(word.label().starts_with(&input));
+        input.push(letter);
+    }
+
+    assert!(word.matches(&input));
+    assert!(!word.matches("םולש"));
+}
+
+#[test]
+fn most_urgent_is_the_rightmost_untyped_word() {
+    let positions = [120.0, 640.0, 300.0, 900.0];
+    let mut words = positions.iter()
+        .map(|x| Word::new("word", Point2 { x: *x, y: 0.0 }, 10.0, Box::new(MockSprite { width: 100.0, height: 100.0}), false).unwrap())
+        .collect::<Vec<Word>>();
+
+    assert_eq!(most_urgent(&words), Some(3));
+
+    words[3].is_typed = true;
+    assert_eq!(most_urgent(&words), Some(1));
+
+    assert_eq!(most_urgent(&[]), None);
+}
+
+#[test]
+fn fresh_word_is_not_expired() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let mut word = Word::new("word", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    word.update(1.0, 1000.0, 100.0);
+
+    assert!(!word.is_expired());
+}
+
+#[test]
+fn typed_word_is_expired() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let mut word = Word::new("word", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    word.is_typed = true;
+
+    assert!(word.is_expired());
+}
+
+#[test]
+fn escaped_word_is_expired() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+    let mut word = Word::new("word", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    assert!(word.update(100.0, 1000.0, 100.0));
+
+    assert!(word.is_expired());
+    assert!(!word.is_typed);
+}
+
+
+#[test]
+fn cached_rect_uses_the_size_given_at_construction() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let word = Word::with_size("word", Point2 { x: 20.0, y: 30.0 }, 10.0, mock_sprite, Vector2 { x: 64.0, y: 18.0 }, false).unwrap();
+
+    assert_eq!(word.cached_rect(), Some(ggez::graphics::Rect::new(20.0, 30.0, 64.0, 18.0)));
+}
+
+#[test]
+fn cached_rect_follows_the_word() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::with_size("word", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, Vector2 { x: 64.0, y: 18.0 }, false).unwrap();
+
+    word.update(1.0, 1000.0, 64.0);
+
+    assert_eq!(word.cached_rect().map(|rect| rect.x), Some(word.pos.x));
+}
+
+#[test]
+fn cached_rect_is_unknown_without_a_size() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let word = Word::new("word", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    assert_eq!(word.cached_rect(), None);
+}
+
+#[test]
+fn shrinking_word_drops_its_last_character() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::new("hello", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    word.is_shrinking = true;
+
+    word.update(Word::SHRINK_INTERVAL - 0.5, f32::MAX, 100.0);
+    assert_eq!(word.label(), "hello");
+
+    word.update(0.5, f32::MAX, 100.0);
+    assert_eq!(word.label(), "hell");
+    assert!(word.matches("hell"));
+}
+
+#[test]
+fn shrinking_word_stops_at_the_min_length() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::new("hello", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    word.is_shrinking = true;
+
+    for _ in 0 .. 10 {
+        word.update(Word::SHRINK_INTERVAL, f32::MAX, 100.0);
+    }
+
+    assert_eq!(word.label().chars().count(), Word::MIN_SHRINK_LEN);
+}
+
+#[test]
+fn shrinking_word_pays_less() {
+    let economy = Economy::default();
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::new("hello", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    word.is_shrinking = true;
+    let full_reward = word.get_reward(&economy);
+
+    word.update(Word::SHRINK_INTERVAL, f32::MAX, 100.0);
+
+    assert!(word.get_reward(&economy) < full_reward);
+}
+
+#[test]
+fn regular_word_keeps_its_label() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::new("hello", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    word.update(Word::SHRINK_INTERVAL * 3.0, f32::MAX, 100.0);
+
+    assert_eq!(word.label(), "hello");
+}
+
+#[test]
+fn replaced_label_is_the_one_matched() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0 });
+    let mut word = Word::with_size("hello", Point2 { x: 20.0, y: 30.0 }, 10.0, mock_sprite, Vector2 { x: 64.0, y: 18.0 }, false).unwrap();
+
+    word.replace_label("world");
+
+    assert!(word.matches("world"));
+    assert!(!word.matches("hello"));
+    assert_eq!(word.pos, Point2 { x: 20.0, y: 30.0 });
+    // the old size no longer fits the text
+    assert_eq!(word.cached_rect(), None);
+}
+
+#[test]
+fn point_popup_floats_up_and_fades() {
+    let mut popup = PointPopup::new(12.5, Point2 { x: 100.0, y: 200.0 }, Color::WHITE);
+    assert_eq!(popup.text, "+12.50");
+    assert_eq!(popup.alpha(), 1.0);
+
+    popup.update(PointPopup::LIFETIME / 2.0);
+
+    assert_eq!(popup.pos.x, 100.0);
+    assert!(popup.pos.y < 200.0);
+    assert!((popup.alpha() - 0.5).abs() < 1e-6);
+    assert!(!popup.is_expired());
+}
+
+#[test]
+fn point_popup_expires_after_its_lifetime() {
+    let mut popup = PointPopup::new(1.0, Point2 { x: 0.0, y: 0.0 }, Color::WHITE);
+
+    popup.update(PointPopup::LIFETIME);
+
+    assert!(popup.is_expired());
+    assert_eq!(popup.alpha(), 0.0);
+}
+
+#[test]
+fn apostrophes_and_hyphens_are_typeable() {
+    assert!(Word::is_typeable('a'));
+    assert!(Word::is_typeable('\''));
+    assert!(Word::is_typeable('-'));
+    assert!(!Word::is_typeable(' '));
+    assert!(!Word::is_typeable('1'));
+}
+
+#[test]
+fn contractions_and_hyphenated_words_match_typed_input() {
+    for label in ["don't", "well-being"] {
+        let mock_sprite = Box::new(MockSprite { width: 100.0, height: 100.0});
+        let word = Word::new(label, Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+        let input = label.chars().filter(|character| Word::is_typeable(*character)).collect::<String>();
+
+        assert!(word.matches(&input));
+    }
+}
+
+#[test]
+fn penalty_popup_shows_the_lost_points() {
+    let popup = PointPopup::new(-5.0, Point2 { x: 0.0, y: 0.0 }, Color::WHITE);
+
+    assert_eq!(popup.text, "-5.00");
+}
+
+#[test]
+fn progress_ratio_follows_the_position() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let word = Word::new("test", Point2 { x: 250.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    assert_eq!(word.progress_ratio(1000.0), 0.25);
+}
+
+#[test]
+fn progress_ratio_is_clamped() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let mut word = Word::new("test", Point2 { x: -50.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    assert_eq!(word.progress_ratio(1000.0), 0.0);
+
+    word.pos.x = 1500.0;
+    assert_eq!(word.progress_ratio(1000.0), 1.0);
+    assert_eq!(word.progress_ratio(0.0), 0.0);
+}
+
+#[test]
+fn accented_word_is_highlighted_at_every_char() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let word = Word::new("café", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    let mut input = String::new();
+    assert!(!word.is_prefixed_by(&input));
+    for letter in "café".chars() {
+        assert!(Word::is_typeable(letter));
+        input.push(letter);
+        assert!(word.is_prefixed_by(&input), "{} isn't a prefix", input);
+    }
+
+    assert!(word.matches(&input));
+    assert!(!word.matches("cafe"));
+    assert!(!word.is_prefixed_by("cafe"));
+}
+
+#[test]
+fn accented_input_can_be_erased_char_by_char() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let word = Word::new("señor", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+
+    let mut input = String::from("señ");
+    input.pop();
+    assert_eq!(input, "se");
+    assert!(word.is_prefixed_by(&input));
+}
+
+#[test]
+fn frozen_color_changing_word_shows_its_bonus() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let mut bonus_word = Word::new("bonus", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, true).unwrap();
+    assert!(!bonus_word.shows_bonus_underline());
+
+    bonus_word.is_color_frozen = true;
+    assert!(bonus_word.shows_bonus_underline());
+
+    bonus_word.is_typed = true;
+    assert!(!bonus_word.shows_bonus_underline());
+
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let mut plain_word = Word::new("plain", Point2 { x: 0.0, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+    plain_word.is_color_frozen = true;
+    assert!(!plain_word.shows_bonus_underline());
+}
+
+#[test]
+fn time_to_escape_divides_the_distance_by_the_speed() {
+    assert_eq!(time_to_escape(300.0, 150.0), Some(2.0));
+    assert_eq!(time_to_escape(-20.0, 150.0), Some(0.0));
+    assert_eq!(time_to_escape(300.0, 0.0), None);
+    assert_eq!(time_to_escape(300.0, -50.0), None);
+    assert_eq!(time_to_escape(300.0, f32::NAN), None);
+}
+
+#[test]
+fn word_time_to_escape_uses_the_escape_edge() {
+    let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+    let word = Word::new("something", Point2 { x: 390.0, y: 0.0 }, 50.0, mock_sprite, false).unwrap();
+
+    // the right edge of the word has 500 pixels to go before it reaches the escape margin
+    let seconds = word.time_to_escape(1000.0, 100.0).unwrap();
+    assert!((seconds - 500.0 / 50.0).abs() < 1e-4);
+}
+
+#[test]
+fn magnet_pulls_back_only_the_most_urgent_word() {
+    let mut words = [(120.0, false), (640.0, false), (900.0, true)].iter()
+        .map(|(x, is_typed)| {
+            let mock_sprite = Box::new(MockSprite { width: 100.0, height: 30.0 });
+            let mut word = Word::new("word", Point2 { x: *x, y: 0.0 }, 10.0, mock_sprite, false).unwrap();
+            word.is_typed = *is_typed;
+            word
+        })
+        .collect::<Vec<Word>>();
+
+    // the typed word is on its way out, the one behind it is the closest to escaping
+    assert_eq!(pull_back_most_urgent(&mut words, 400.0), Some((1, 400.0)));
+    assert_eq!(words[1].pos.x, 240.0);
+    assert_eq!(words[0].pos.x, 120.0);
+    assert_eq!(words[2].pos.x, 900.0);
+
+    // never past the left edge, the next update moves on from there
+    assert_eq!(pull_back_most_urgent(&mut words, 400.0), Some((1, 240.0)));
+    assert_eq!(words[1].pos.x, 0.0);
+    words[1].reset_translation();
+    assert_eq!(words[1].pos.x, 0.0);
+}
+
+#[test]
+fn magnet_has_nothing_to_pull_on_an_empty_board() {
+    let mut words: Vec<Word> = Vec::new();
+
+    assert_eq!(pull_back_most_urgent(&mut words, 400.0), None);
+}
+
+#[test]
+fn input_equal_to_a_word_on_screen_matches_it() {
+    let mut words = ["coffee", "tea", "cocoa"].iter()
+        .map(|label| Word::new(label, Point2 { x: 0.0, y: 0.0 }, 10.0, Box::new(MockSprite { width: 100.0, height: 30.0 }), false).unwrap())
+        .collect::<Vec<Word>>();
+
+    assert_eq!(matching_word(&words, "tea", false), Some(1));
+    assert_eq!(matching_word(&words, "te", false), None);
+    assert_eq!(matching_word(&words, "", false), None);
+
+    // in order only the oldest word counts
+    assert_eq!(matching_word(&words, "tea", true), None);
+    assert_eq!(matching_word(&words, "coffee", true), Some(0));
+
+    words.clear();
+    assert_eq!(matching_word(&words, "tea", true), None);
 }
\ No newline at end of file
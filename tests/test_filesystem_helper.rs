@@ -0,0 +1,73 @@
+use ggez::graphics::Color;
+
+use type_racer::filesystem_helper::{ dedup_lines, filter_by_length, resource_dirs, score_values, split_color_header };
+
+use std::path::PathBuf;
+
+#[test]
+fn dedup_lines_keeps_first_occurrences_in_order() {
+    let lines = ["tree", "rust", "tree", "ggez", "rust", "tree"].iter().map(|line| line.to_string()).collect();
+
+    let (unique, removed) = dedup_lines(lines);
+
+    assert_eq!(unique, vec!["tree", "rust", "ggez"]);
+    assert_eq!(removed, 3);
+}
+
+#[test]
+fn dedup_lines_without_duplicates() {
+    let lines = vec![String::from("tree"), String::from("rust")];
+
+    assert_eq!(dedup_lines(lines.clone()), (lines, 0));
+}
+
+#[test]
+fn resource_dirs_prefer_the_manifest_dir() {
+    let exe_path = PathBuf::from("/opt/type_racer/type_racer");
+
+    assert_eq!(resource_dirs(Some("/src/type_racer"), Some(&exe_path)),
+               vec![PathBuf::from("/src/type_racer/resources"), PathBuf::from("/opt/type_racer/resources")]);
+    assert_eq!(resource_dirs(None, Some(&exe_path)), vec![PathBuf::from("/opt/type_racer/resources")]);
+    assert!(resource_dirs(None, None).is_empty());
+}
+
+#[test]
+fn filter_by_length_counts_characters() {
+    let lines = ["tree", "extraordinary", "ключ", "a"].iter().map(|line| line.to_string()).collect();
+
+    let (kept, removed) = filter_by_length(lines, 4);
+
+    assert_eq!(kept, vec!["tree", "ключ", "a"]);
+    assert_eq!(removed, 1);
+}
+
+
+#[test]
+fn color_header_sets_the_words_color() {
+    let lines = vec![String::from("#color 255 128 0"), String::from("apple"), String::from("pear")];
+    let (color, words) = split_color_header(lines);
+
+    assert_eq!(color, Some(Color::from_rgb(255, 128, 0)));
+    assert_eq!(words, vec![String::from("apple"), String::from("pear")]);
+}
+
+#[test]
+fn dictionary_without_a_color_header() {
+    let lines = vec![String::from("apple"), String::from("pear")];
+
+    assert_eq!(split_color_header(lines.clone()), (None, lines));
+}
+
+#[test]
+fn malformed_color_header_is_still_dropped() {
+    let lines = vec![String::from("#color 300 0"), String::from("apple")];
+
+    assert_eq!(split_color_header(lines), (None, vec![String::from("apple")]));
+}
+
+#[test]
+fn score_values_read_the_last_field() {
+    let lines = ["ada 1520.50", "grace hopper 300.00", "broken", "bob notanumber"].iter().map(|line| line.to_string()).collect::<Vec<String>>();
+
+    assert_eq!(score_values(&lines), vec![1520.5, 300.0]);
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+use type_racer::menu::*;
+
+#[test]
+fn menu_select_next_wraps_around() {
+    assert_eq!(select_next(0, 3), 1);
+    assert_eq!(select_next(2, 3), 0);
+}
+
+#[test]
+fn menu_select_previous_wraps_around() {
+    assert_eq!(select_previous(1, 3), 0);
+    assert_eq!(select_previous(0, 3), 2);
+}
+
+#[test]
+fn menu_without_options_stays_at_zero() {
+    assert_eq!(select_next(0, 0), 0);
+    assert_eq!(select_previous(0, 0), 0);
+}
+
+#[test]
+fn menu_visible_range_keeps_the_selection_in_view() {
+    assert_eq!(visible_range(3, 5, 10), (0, 5));
+    assert_eq!(visible_range(0, 14, 6), (0, 6));
+    assert_eq!(visible_range(7, 14, 6), (4, 10));
+    assert_eq!(visible_range(13, 14, 6), (8, 14));
+}
+
+
+#[test]
+fn restart_needs_the_whole_word() {
+    assert!(confirms_restart("again"));
+    assert!(confirms_restart("Again"));
+    assert!(!confirms_restart("agai"));
+    assert!(!confirms_restart(""));
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use type_racer::passage::Passage;
+
+use std::path::PathBuf;
+
+#[test]
+fn passage_spawns_words_in_order() {
+    let mut passage = Passage::from_text("the quick\nbrown  fox\n");
+
+    assert_eq!(passage.spawn_next().as_deref(), Some("the"));
+    assert_eq!(passage.spawn_next().as_deref(), Some("quick"));
+    assert_eq!(passage.spawn_next().as_deref(), Some("brown"));
+    assert_eq!(passage.spawn_next().as_deref(), Some("fox"));
+    assert_eq!(passage.spawn_next(), None);
+}
+
+#[test]
+fn passage_completes_once_every_word_is_finished() {
+    let mut passage = Passage::from_text("the quick fox");
+
+    passage.spawn_next();
+    passage.spawn_next();
+    passage.finish_word();
+    passage.finish_word();
+    // only spawned words can be finished
+    passage.finish_word();
+    assert_eq!(passage.progress(), (2, 3));
+    assert!(!passage.is_complete());
+
+    passage.spawn_next();
+    passage.finish_word();
+    assert_eq!(passage.progress(), (3, 3));
+    assert!(passage.is_complete());
+}
+
+#[test]
+fn passages_are_the_sorted_text_files() {
+    let paths = ["/passages/rust.txt", "/passages/notes.md", "/passages/fox.txt", "/passages/rust.txt"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    assert_eq!(Passage::passages_from(paths), vec!["/passages/fox.txt", "/passages/rust.txt"]);
+}
+
+#[test]
+fn unfinished_word_reopens_the_passage() {
+    let mut passage = Passage::from_text("fox");
+    passage.spawn_next();
+    passage.finish_word();
+
+    passage.unfinish_word();
+
+    assert_eq!(passage.progress(), (0, 1));
+    assert!(!passage.is_complete());
+}
@@ -0,0 +1,324 @@
+use ggez::{ Context, GameResult };
+use ggez::graphics::Color;
+use ggez::mint::Point2;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use type_racer::assets::Sprite;
+use type_racer::difficulty::Difficulty;
+use type_racer::economy::{ Economy, Wallet };
+use type_racer::entities::Word;
+use type_racer::passage::Passage;
+use type_racer::power_up::*;
+use type_racer::stats::{ Outcome, RunStats };
+
+#[derive(Debug)]
+struct MockSprite;
+
+impl Sprite for MockSprite {
+    fn draw(&mut self, _top_left: Point2<f32>, _color: Color, _ctx: &mut Context) -> GameResult<()> {
+        Ok(())
+    }
+
+    fn width(&self, _ctx: &mut Context) -> f32 {
+        100.0
+    }
+
+    fn height(&self, _ctx: &mut Context) -> f32 {
+        30.0
+    }
+
+    fn set_text(&mut self, _text: &str) {
+    }
+}
+
+fn board(labels: &[&str]) -> Vec<Word> {
+    labels.iter()
+        .map(|label| Word::new(label, Point2 { x: 0.0, y: 0.0 }, 100.0, Box::new(MockSprite), false).unwrap())
+        .collect()
+}
+
+#[test]
+fn charge_deducts_the_tax() {
+    let economy = Economy::default();
+
+    for power_up in [PowerUp::ExtraLife, PowerUp::RemoveWords, PowerUp::SlowSpawn, PowerUp::Shield] {
+        let mut wallet = Wallet::new(1200.0);
+
+        assert!(power_up.charge(&economy, 0, &mut wallet));
+        assert_eq!(wallet.cash(), 1200.0 - power_up.tax(&economy));
+    }
+}
+
+#[test]
+fn charge_is_a_no_op_when_unaffordable() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.slow_word_spawn_tax - 1.0);
+
+    assert!(!PowerUp::SlowSpawn.charge(&economy, 0, &mut wallet));
+    assert_eq!(wallet.cash(), economy.slow_word_spawn_tax - 1.0);
+}
+
+#[test]
+fn slow_spawn_halves_the_speed_up() {
+    assert_eq!(slowed_speed_up(0.6), 0.3);
+    assert_eq!(slowed_speed_up(0.0), 0.0);
+}
+
+#[test]
+fn removal_takes_every_word_when_few_are_left() {
+    let mut rng = StdRng::seed_from_u64(3);
+
+    assert_eq!(removal_indexes(&mut rng, 2, 2), vec![0, 1]);
+    assert!(removal_indexes(&mut rng, 0, 2).is_empty());
+}
+
+#[test]
+fn removal_takes_distinct_words() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut indexes = removal_indexes(&mut rng, 10, 2);
+    indexes.sort();
+    indexes.dedup();
+
+    assert_eq!(indexes.len(), 2);
+    assert!(indexes.iter().all(|index| *index < 10));
+}
+
+#[test]
+fn shield_goes_before_a_life() {
+    assert_eq!(escape_cost(true, 0.0), EscapeCost::Shield);
+    assert_eq!(escape_cost(false, 0.0), EscapeCost::Life);
+}
+
+#[test]
+fn warmup_escape_costs_nothing() {
+    assert_eq!(escape_cost(false, 3.0), EscapeCost::Free);
+    // the shield is kept for after the warmup
+    assert_eq!(escape_cost(true, 3.0), EscapeCost::Free);
+}
+
+#[test]
+fn escape_after_the_warmup_costs_a_life() {
+    let mut warmup_left = Difficulty::Normal.warmup_duration();
+    assert_eq!(escape_cost(false, warmup_left), EscapeCost::Free);
+
+    warmup_left -= Difficulty::Normal.warmup_duration();
+    assert_eq!(escape_cost(false, warmup_left), EscapeCost::Life);
+}
+
+#[test]
+fn hard_has_no_warmup() {
+    assert_eq!(escape_cost(false, Difficulty::Hard.warmup_duration()), EscapeCost::Life);
+}
+
+
+#[test]
+fn escalated_tax_grows_by_half_per_purchase() {
+    assert_eq!(escalated_tax(300.0, 0), 300.0);
+    assert_eq!(escalated_tax(300.0, 1), 450.0);
+    assert_eq!(escalated_tax(300.0, 4), 900.0);
+}
+
+#[test]
+fn charge_takes_the_escalated_price() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.buy_life_tax * 1.5);
+
+    assert!(!PowerUp::ExtraLife.charge(&economy, 2, &mut wallet));
+    assert!(PowerUp::ExtraLife.charge(&economy, 1, &mut wallet));
+    assert_eq!(wallet.cash(), 0.0);
+}
+
+#[test]
+fn timed_slow_spawn_gives_back_what_it_took() {
+    let before = 0.6;
+    let slowed = slowed_speed_up(before);
+    let taken = before - slowed;
+
+    assert_eq!(restored_speed_up(slowed, taken), before);
+    // the ramp gathered during the effect is kept
+    assert_eq!(restored_speed_up(slowed + 0.25, taken), before + 0.25);
+}
+
+#[test]
+fn every_purchase_counts_for_its_own_power_up() {
+    let mut counts = PurchaseCounts::default();
+
+    counts.record(PowerUp::ExtraLife);
+    counts.record(PowerUp::RemoveWords);
+    counts.record(PowerUp::RemoveWords);
+    counts.record(PowerUp::SlowSpawn);
+
+    assert_eq!(counts, PurchaseCounts { extra_life: 1, remove_words: 2, slow_spawn: 1, shield: 0, magnet: 0 });
+    assert_eq!(counts.count(PowerUp::RemoveWords), 2);
+}
+
+#[test]
+fn undone_purchase_is_taken_back_from_its_counter() {
+    let mut counts = PurchaseCounts::default();
+
+    counts.record(PowerUp::Shield);
+    counts.undo(PowerUp::Shield);
+    counts.undo(PowerUp::ExtraLife);
+
+    assert_eq!(counts, PurchaseCounts::default());
+}
+
+#[test]
+fn panic_clear_takes_all_the_cash_and_every_word() {
+    let mut wallet = Wallet::new(420.0);
+    let mut words = vec!["one", "two", "three"];
+
+    let panic = panic_clear(&mut wallet, &mut words, 200.0).unwrap();
+    assert_eq!(panic.spent, 420.0);
+    assert_eq!(panic.cleared, vec!["one", "two", "three"]);
+    assert_eq!(wallet.cash(), 0.0);
+    assert!(words.is_empty());
+}
+
+#[test]
+fn panic_clear_leaves_both_untouched_when_it_cant_go_through() {
+    let mut wallet = Wallet::new(150.0);
+    let mut words = vec!["one", "two"];
+    assert!(panic_clear(&mut wallet, &mut words, 200.0).is_none());
+    assert_eq!(wallet.cash(), 150.0);
+    assert_eq!(words.len(), 2);
+
+    // nothing to clear isn't worth the cash
+    let mut empty: Vec<&str> = Vec::new();
+    assert!(panic_clear(&mut wallet, &mut empty, 100.0).is_none());
+    assert_eq!(wallet.cash(), 150.0);
+
+    let mut broke = Wallet::new(0.0);
+    assert!(panic_clear(&mut broke, &mut words, 0.0).is_none());
+    assert_eq!(words.len(), 2);
+}
+
+#[test]
+fn held_power_up_key_buys_once() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.buy_life_tax * 3.0);
+    let mut throttle = PurchaseThrottle::new();
+
+    // the first press, the key repeats of the held key, and a bounce right after the press
+    let presses = [(1.0, false), (1.03, true), (1.06, true), (1.09, true), (1.1, false)];
+    let bought = presses.iter()
+        .filter(|(time, repeat)| throttle.press(PowerUp::ExtraLife, *time, *repeat))
+        .filter(|_| PowerUp::ExtraLife.charge(&economy, 0, &mut wallet))
+        .count();
+
+    assert_eq!(bought, 1);
+    assert_eq!(wallet.cash(), economy.buy_life_tax * 2.0);
+}
+
+#[test]
+fn purchase_throttle_lets_through_after_the_cooldown() {
+    let mut throttle = PurchaseThrottle::new();
+
+    assert!(throttle.press(PowerUp::Shield, 0.0, false));
+    assert!(!throttle.press(PowerUp::Shield, PURCHASE_COOLDOWN / 2.0, false));
+    assert!(throttle.press(PowerUp::Shield, PURCHASE_COOLDOWN, false));
+    // every power up has its own cooldown
+    assert!(throttle.press(PowerUp::SlowSpawn, PURCHASE_COOLDOWN, false));
+}
+
+#[test]
+fn extra_life_adds_a_life_for_its_price() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.buy_life_tax);
+    let mut lifes = 2;
+
+    assert!(buy_extra_life(&economy, 0, &mut wallet, &mut lifes));
+    assert_eq!(lifes, 3);
+    assert_eq!(wallet.cash(), 0.0);
+
+    assert!(!buy_extra_life(&economy, 0, &mut wallet, &mut lifes));
+    assert_eq!(lifes, 3);
+}
+
+#[test]
+fn shield_is_bought_once_at_a_time() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.shield_tax * 2.0);
+    let mut shield_active = false;
+
+    assert!(buy_shield(&economy, 0, &mut wallet, &mut shield_active));
+    assert!(shield_active);
+
+    assert!(!buy_shield(&economy, 0, &mut wallet, &mut shield_active));
+    assert_eq!(wallet.cash(), economy.shield_tax);
+}
+
+#[test]
+fn unaffordable_shield_stays_off() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.shield_tax - 1.0);
+    let mut shield_active = false;
+
+    assert!(!buy_shield(&economy, 0, &mut wallet, &mut shield_active));
+    assert!(!shield_active);
+    assert_eq!(wallet.cash(), economy.shield_tax - 1.0);
+}
+
+#[test]
+fn word_removal_takes_the_words_with_their_rewards() {
+    let economy = Economy::default();
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut wallet = Wallet::new(economy.remove_words_tax);
+    let mut words = board(&["alpha", "beta", "gamma"]);
+    let mut stats = RunStats::new(Difficulty::Normal);
+    let mut passage = Passage::from_text("alpha beta gamma");
+    for _ in 0 .. 3 {
+        passage.spawn_next();
+    }
+
+    let removed = buy_remove_words(&mut rng, &economy, 0, &mut wallet, &mut words, &mut stats, Some(&mut passage));
+
+    assert_eq!(removed.len(), REMOVE_WORDS_COUNT);
+    assert_eq!(words.len(), 3 - REMOVE_WORDS_COUNT);
+    assert_eq!(wallet.cash(), 0.0);
+    assert!(removed[0].0 > removed[1].0);
+    for (_, word, reward) in removed.iter() {
+        assert_eq!(*reward, word.get_reward(&economy));
+    }
+    assert!(stats.outcomes().iter().all(|outcome| outcome.outcome == Outcome::Removed));
+    assert_eq!(stats.outcomes().len(), REMOVE_WORDS_COUNT);
+    assert_eq!(passage.progress(), (REMOVE_WORDS_COUNT, 3));
+}
+
+#[test]
+fn word_removal_is_a_no_op_on_an_empty_board_or_an_empty_wallet() {
+    let economy = Economy::default();
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut stats = RunStats::new(Difficulty::Normal);
+
+    let mut wallet = Wallet::new(economy.remove_words_tax);
+    let mut words = Vec::new();
+    assert!(buy_remove_words(&mut rng, &economy, 0, &mut wallet, &mut words, &mut stats, None).is_empty());
+    assert_eq!(wallet.cash(), economy.remove_words_tax);
+
+    let mut wallet = Wallet::new(economy.remove_words_tax - 1.0);
+    let mut words = board(&["alpha", "beta"]);
+    assert!(buy_remove_words(&mut rng, &economy, 0, &mut wallet, &mut words, &mut stats, None).is_empty());
+    assert_eq!(words.len(), 2);
+    assert!(stats.outcomes().is_empty());
+}
+
+#[test]
+fn slow_spawn_buy_keeps_what_it_took() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.slow_word_spawn_tax * 2.0);
+
+    assert_eq!(buy_slow_spawn(&economy, 0, &mut wallet, 0.6, false), Some(SlowSpawnBuy { speed_up_before: 0.6, speed_up: 0.3, duration: 0.0, taken: 0.0 }));
+    assert_eq!(buy_slow_spawn(&economy, 0, &mut wallet, 0.6, true), Some(SlowSpawnBuy { speed_up_before: 0.6, speed_up: 0.3, duration: SLOW_SPAWN_DURATION, taken: 0.3 }));
+    assert_eq!(wallet.cash(), 0.0);
+}
+
+#[test]
+fn unaffordable_slow_spawn_changes_nothing() {
+    let economy = Economy::default();
+    let mut wallet = Wallet::new(economy.slow_word_spawn_tax - 1.0);
+
+    assert_eq!(buy_slow_spawn(&economy, 0, &mut wallet, 0.6, true), None);
+    assert_eq!(wallet.cash(), economy.slow_word_spawn_tax - 1.0);
+}
\ No newline at end of file
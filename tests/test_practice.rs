@@ -0,0 +1,45 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use type_racer::practice::*;
+
+fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn missed_words_round_trip() {
+    let mut missed = MissedWords::new();
+    missed.record("rhythm");
+    missed.record("rhythm");
+    missed.record("queue");
+
+    assert_eq!(MissedWords::from_lines(&missed.to_lines()), missed);
+    assert_eq!(missed.count("rhythm"), 2);
+    assert_eq!(missed.count("queue"), 1);
+}
+
+#[test]
+fn malformed_missed_words_lines_are_skipped() {
+    let missed = MissedWords::from_lines(&lines(&["rhythm 3", "queue", "gauge many", "", "rhythm 1"]));
+
+    assert_eq!(missed.to_lines(), vec!["rhythm 4"]);
+}
+
+#[test]
+fn nothing_to_practice_without_missed_words() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    assert!(MissedWords::new().is_empty());
+    assert_eq!(MissedWords::new().pick(&mut rng), None);
+}
+
+#[test]
+fn picks_are_weighted_by_the_miss_count() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let missed = MissedWords::from_lines(&lines(&["often 9", "rarely 1"]));
+
+    let often = (0 .. 1000).filter(|_| missed.pick(&mut rng).as_deref() == Some("often")).count();
+
+    assert!(often > 850 && often < 950, "picked often {} times", often);
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+use type_racer::power_up::PowerUp;
+use type_racer::replay::*;
+use type_racer::stats::SUBMIT;
+
+fn short_recording() -> Recording {
+    let mut recording = Recording::new();
+    recording.record_spawn(0.5, "hello", 120.0, 150.0, false, false);
+    recording.record_key(1.0, 'h');
+    recording.record_key(1.25, 'e');
+    recording.record_spawn(2.0, "world", 300.0, 200.0, true, true);
+
+    recording
+}
+
+#[test]
+fn recording_round_trips_through_json() {
+    let recording = short_recording();
+    let json = recording.to_json().unwrap();
+
+    assert_eq!(Recording::from_json(&json).unwrap(), recording);
+}
+
+#[test]
+fn recording_keeps_the_event_order() {
+    let times = short_recording().events().iter().map(|event| event.time()).collect::<Vec<f32>>();
+
+    assert_eq!(times, vec![0.5, 1.0, 1.25, 2.0]);
+}
+
+#[test]
+fn playback_hands_out_the_due_events_once() {
+    let mut playback = Playback::new(short_recording());
+
+    assert!(playback.due(0.0).is_empty());
+    assert_eq!(playback.due(1.0), vec![
+        ReplayEvent::Spawn { time: 0.5, label: String::from("hello"), y: 120.0, speed: 150.0, is_color_changing: false, is_shrinking: false },
+        ReplayEvent::Key { time: 1.0, key: 'h' }
+    ]);
+    assert!(playback.due(1.0).is_empty());
+    assert!(!playback.is_finished());
+
+    assert_eq!(playback.due(5.0).len(), 2);
+    assert!(playback.is_finished());
+}
+
+#[test]
+fn malformed_recording_is_an_error() {
+    assert!(Recording::from_json("not a replay").is_err());
+}
+
+#[test]
+fn spawns_recorded_without_shrinking_still_load() {
+    let json = r#"{"events":[{"Spawn":{"time":0.5,"label":"hello","y":120.0,"speed":150.0,"is_color_changing":false}}]}"#;
+    let recording = Recording::from_json(json).unwrap();
+
+    assert_eq!(recording.events(), &[ReplayEvent::Spawn { time: 0.5, label: String::from("hello"), y: 120.0, speed: 150.0, is_color_changing: false, is_shrinking: false }]);
+}
+
+#[test]
+fn failed_submit_round_trips_as_a_key() {
+    let mut recording = short_recording();
+    recording.record_key(1.5, SUBMIT);
+    let json = recording.to_json().unwrap();
+
+    let events = Recording::from_json(&json).unwrap().events().to_vec();
+    assert_eq!(events.last(), Some(&ReplayEvent::Key { time: 1.5, key: SUBMIT }));
+}
+
+#[test]
+fn buys_undos_and_panics_round_trip_with_the_removal_seed() {
+    let mut recording = short_recording();
+    recording.set_removal_seed(42);
+    recording.record_power_up(2.5, PowerUp::RemoveWords);
+    recording.record_undo(2.75);
+    recording.record_panic(3.0);
+    let loaded = Recording::from_json(&recording.to_json().unwrap()).unwrap();
+
+    assert_eq!(loaded, recording);
+    assert_eq!(loaded.removal_seed(), 42);
+    assert_eq!(&loaded.events()[4..], &[ReplayEvent::PowerUp { time: 2.5, power_up: PowerUp::RemoveWords }, ReplayEvent::Undo { time: 2.75 }, ReplayEvent::Panic { time: 3.0 }]);
+}
+
+#[test]
+fn recordings_without_a_removal_seed_still_load() {
+    let json = r#"{"events":[{"Key":{"time":1.0,"key":"h"}}]}"#;
+
+    assert_eq!(Recording::from_json(json).unwrap().removal_seed(), 0);
+}
\ No newline at end of file
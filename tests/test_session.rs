@@ -0,0 +1,72 @@
+use type_racer::difficulty::Difficulty;
+use type_racer::power_up::{ PowerUp, PurchaseCounts };
+use type_racer::session::*;
+use type_racer::stats::RunStats;
+
+fn snapshot() -> SessionSnapshot {
+    let mut stats = RunStats::new(Difficulty::Hard);
+    stats.advance(42.5);
+    stats.record_spawned("hello");
+    stats.record_spawned("world");
+    stats.record_typed("hello");
+
+    let mut purchases = PurchaseCounts::default();
+    purchases.record(PowerUp::Shield);
+
+    SessionSnapshot {
+        version: SessionSnapshot::VERSION,
+        difficulty: Difficulty::Hard,
+        words: vec![WordSnapshot { label: String::from("world"), x: 310.5, y: 120.0, speed: 180.0, is_color_changing: true, is_shrinking: false }],
+        next_word: Some(String::from("again")),
+        cash: 75.25,
+        score: 130.0,
+        remaining_lifes: 2,
+        shield_active: true,
+        ranked: true,
+        warmup_time: 0.0,
+        time_until_next_word: 1.2,
+        game_speed_up: 0.3,
+        time_until_shake: 8.0,
+        slow_spawn_time: 0.0,
+        slow_spawn_taken: 0.0,
+        wave: 3,
+        wave_spawns_left: 4,
+        wave_rest: 0.0,
+        wave_escaped: false,
+        spawned_words: 2,
+        purchases,
+        stats
+    }
+}
+
+#[test]
+fn snapshot_round_trips_through_json() {
+    let snapshot = snapshot();
+    let json = snapshot.to_json().unwrap();
+
+    assert_eq!(SessionSnapshot::from_json(&json), Some(snapshot));
+}
+
+#[test]
+fn snapshot_of_another_version_is_discarded() {
+    let mut snapshot = snapshot();
+    snapshot.version = SessionSnapshot::VERSION + 1;
+    let json = snapshot.to_json().unwrap();
+
+    assert_eq!(SessionSnapshot::from_json(&json), None);
+}
+
+#[test]
+fn unreadable_snapshot_is_discarded() {
+    assert_eq!(SessionSnapshot::from_json(""), None);
+    assert_eq!(SessionSnapshot::from_json("{\"version\": 1}"), None);
+}
+
+#[test]
+fn snapshot_from_before_the_magnet_is_discarded() {
+    let mut snapshot = snapshot();
+    snapshot.version = 1;
+    let json = snapshot.to_json().unwrap();
+
+    assert_eq!(SessionSnapshot::from_json(&json), None);
+}
\ No newline at end of file
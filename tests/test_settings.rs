@@ -0,0 +1,93 @@
+use type_racer::difficulty::Difficulty;
+use type_racer::settings::Settings;
+
+#[test]
+fn settings_round_trip() {
+    let settings = Settings {
+        color_changing_words: false,
+        survival_summary: true,
+        difficulty: Difficulty::Hard,
+        next_word_preview: true,
+        heart_lifes: true,
+        starting_cash: 350.0,
+        show_kps: true,
+        info_key: '?',
+        volume_key: '+',
+        dedup_words: true,
+        focus_mode: true,
+        keystroke_log: true,
+        music_track: String::from("/calm-background-music.wav"),
+        adaptive_spawn: true,
+        passage: String::from("/passages/fox.txt"),
+        speed_ramp: false,
+        fullscreen: true,
+        max_word_len: 8,
+        max_chars_per_frame: 2,
+        breathing_background: true,
+        shrinking_words: true,
+        spawn_retries: 3,
+        clear_input_on_escape: true,
+        double_speed: true,
+        show_cash_rate: true,
+        avoid_repeats: false,
+        escalating_prices: true,
+        show_length_histogram: true,
+        timed_slow_spawn: true,
+        safe_start: true,
+        last_stand: true,
+        miss_penalty: 25.0,
+        show_minimap: true,
+        practice_weak_words: true,
+        typed_log: true,
+        max_visible_words: 6,
+        waves: true,
+        show_cash_delta: false,
+        strict_submit: true,
+        first_words_grace: true,
+        resume_session: true,
+        panic_min_cash: 250.0,
+        show_escape_times: true,
+        auto_difficulty: true,
+        speedrun_hud: true,
+        spawn_lanes: 5
+    };
+
+    assert_eq!(Settings::from_lines(&settings.to_lines()), settings);
+}
+
+#[test]
+fn settings_skip_malformed_lines() {
+    let lines = vec![
+        String::from("unknown_key 42"),
+        String::from("color_changing_words maybe"),
+        String::from("garbage")
+    ];
+
+    assert_eq!(Settings::from_lines(&lines), Settings::default());
+}
+
+
+#[test]
+fn typeable_info_key_keeps_the_default() {
+    let lines = vec![String::from("info_key e")];
+    assert_eq!(Settings::from_lines(&lines).info_key, Settings::default().info_key);
+
+    let lines = vec![String::from("info_key ?")];
+    assert_eq!(Settings::from_lines(&lines).info_key, '?');
+}
+
+#[test]
+fn volume_key_can_be_neither_typeable_nor_the_info_key() {
+    let lines = vec![String::from("volume_key e")];
+    assert_eq!(Settings::from_lines(&lines).volume_key, Settings::default().volume_key);
+
+    let lines = vec![String::from("info_key ?"), String::from("volume_key ?")];
+    let settings = Settings::from_lines(&lines);
+    assert_eq!(settings.info_key, '?');
+    assert_eq!(settings.volume_key, Settings::default().volume_key);
+
+    let lines = vec![String::from("info_key =")];
+    let settings = Settings::from_lines(&lines);
+    assert_eq!(settings.info_key, '=');
+    assert_ne!(settings.volume_key, '=');
+}
\ No newline at end of file
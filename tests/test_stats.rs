@@ -0,0 +1,285 @@
+use type_racer::difficulty::Difficulty;
+use type_racer::power_up::PurchaseCounts;
+use type_racer::stats::*;
+
+use std::collections::VecDeque;
+
+#[test]
+fn survival_message_by_elapsed_time() {
+    assert_eq!(survival_message(0.0), "That was quick, hang in there a little longer!");
+    assert_eq!(survival_message(59.9), "That was quick, hang in there a little longer!");
+    assert_eq!(survival_message(60.0), "Steady hands, keep it up!");
+    assert_eq!(survival_message(200.0), "What an endurance, impressive!");
+    assert_eq!(survival_message(300.0), "Unbreakable, the words just can't get you :)");
+}
+
+#[test]
+fn flavor_message_by_typed_words() {
+    assert_eq!(flavor_message(Difficulty::Normal, 0), "Bummer, I know you can do better :) Try again!");
+    assert_eq!(flavor_message(Difficulty::Normal, 5), "Not very bad!");
+    assert_eq!(flavor_message(Difficulty::Normal, 49), "Amazing, but can you do better?");
+    assert_eq!(flavor_message(Difficulty::Normal, 50), "You're a madman, niiice :)");
+}
+
+#[test]
+fn flavor_message_scales_with_difficulty() {
+    assert_eq!(flavor_message(Difficulty::Easy, 30), "Amazing, but can you do better?");
+    assert_eq!(flavor_message(Difficulty::Hard, 30), "You're a madman, niiice :)");
+    assert_eq!(flavor_message(Difficulty::Easy, 5), "Bummer, I know you can do better :) Try again!");
+}
+
+#[test]
+fn run_report_from_stats() {
+    let mut stats = RunStats::new(Difficulty::Hard);
+    stats.advance(60.0);
+    stats.record_typed("hello");
+    stats.record_typed("world");
+    stats.record_escaped("escape");
+    stats.record_removed("gone");
+
+    let report = stats.report(PurchaseCounts { extra_life: 1, ..PurchaseCounts::default() });
+
+    assert_eq!(report.words_typed, 2);
+    assert!((report.wpm - 2.0).abs() < f32::EPSILON);
+    assert!((report.accuracy - 2.0 / 3.0).abs() < f32::EPSILON);
+    assert!((report.elapsed - 60.0).abs() < f32::EPSILON);
+    assert_eq!(report.difficulty, Difficulty::Hard);
+    assert_eq!(report.purchases.extra_life, 1);
+    assert_eq!(report.words, vec![
+        WordOutcome { word: String::from("hello"), outcome: Outcome::Typed },
+        WordOutcome { word: String::from("world"), outcome: Outcome::Typed },
+        WordOutcome { word: String::from("escape"), outcome: Outcome::Escaped },
+        WordOutcome { word: String::from("gone"), outcome: Outcome::Removed }
+    ]);
+}
+
+#[test]
+fn empty_run_has_zero_rates() {
+    let stats = RunStats::new(Difficulty::Normal);
+
+    assert_eq!(stats.wpm(), 0.0);
+    assert_eq!(stats.accuracy(), 0.0);
+}
+
+
+#[test]
+fn milestone_every_interval() {
+    assert!(!is_milestone(0, 25));
+    assert!(!is_milestone(24, 25));
+    assert!(is_milestone(25, 25));
+    assert!(!is_milestone(26, 25));
+    assert!(is_milestone(50, 25));
+    assert!(!is_milestone(25, 0));
+}
+
+#[test]
+fn keystrokes_counted_inside_the_window() {
+    let timestamps: VecDeque<f32> = vec![0.5, 1.2, 1.6, 2.0, 2.4].into_iter().collect();
+
+    assert_eq!(keystrokes_in_window(&timestamps, 2.4, 1.0), 3);
+    assert_eq!(keystrokes_in_window(&timestamps, 10.0, 1.0), 0);
+    assert_eq!(keystrokes_in_window(&timestamps, 1.0, 1.0), 1);
+    assert_eq!(keystrokes_in_window(&VecDeque::new(), 1.0, 1.0), 0);
+}
+#[test]
+fn keystroke_csv_has_a_row_per_keystroke() {
+    let keystrokes = [(0.5, 'a'), (0.75, BACKSPACE), (1.0, 'B'), (1.5, SUBMIT)];
+
+    assert_eq!(format_keystroke_csv(&keystrokes), "time,key\n0.500,a\n0.750,backspace\n1.000,B\n1.500,enter\n");
+    assert_eq!(format_keystroke_csv(&[]), "time,key\n");
+}
+
+#[test]
+fn forget_removed_drops_the_latest_removal_only() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.record_removed("gone");
+    stats.record_typed("gone");
+    stats.record_removed("gone");
+
+    stats.forget_removed("gone");
+    stats.forget_removed("missing");
+
+    assert_eq!(stats.outcomes(), &[
+        WordOutcome { word: String::from("gone"), outcome: Outcome::Removed },
+        WordOutcome { word: String::from("gone"), outcome: Outcome::Typed }
+    ]);
+}
+
+#[test]
+fn streak_breaks_on_escape_but_not_on_shield() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.record_typed("one");
+    stats.record_typed("two");
+    stats.record_shielded("three");
+    stats.record_typed("four");
+    assert_eq!(stats.streak, 3);
+    assert_eq!(stats.escaped_words, 0);
+
+    stats.record_escaped("five");
+    stats.record_typed("six");
+    assert_eq!(stats.streak, 1);
+    assert_eq!(stats.best_streak, 3);
+}
+
+
+#[test]
+fn input_burst_over_the_limit() {
+    assert!(is_input_burst(5, 4));
+    assert!(!is_input_burst(4, 4));
+    assert!(!is_input_burst(1, 4));
+}
+
+#[test]
+fn input_burst_guard_can_be_turned_off() {
+    assert!(!is_input_burst(100, 0));
+}
+
+#[test]
+fn leaderboard_name_marks_double_speed_runs() {
+    assert_eq!(leaderboard_name("george", false), "george");
+    assert_eq!(leaderboard_name("george", true), "george[x1.5]");
+}
+
+#[test]
+fn cash_per_minute_over_the_elapsed_time() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.advance(30.0);
+    stats.record_cash(40.0);
+    stats.record_cash(10.0);
+
+    assert_eq!(stats.cash_per_minute(), 100.0);
+}
+
+#[test]
+fn cash_per_minute_before_the_run_starts() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.record_cash(40.0);
+
+    assert_eq!(stats.cash_per_minute(), 0.0);
+}
+
+#[test]
+fn length_histogram_buckets_by_length() {
+    let mut histogram = LengthHistogram::default();
+    for length in [3, 5, 5, 1, 0] {
+        histogram.record(length);
+    }
+
+    assert_eq!(histogram.counts()[0], 1);
+    assert_eq!(histogram.counts()[2], 1);
+    assert_eq!(histogram.counts()[4], 2);
+    assert_eq!(histogram.counts().iter().sum::<u32>(), 4);
+    assert_eq!(histogram.max_count(), 2);
+}
+
+#[test]
+fn length_histogram_caps_long_words() {
+    let mut histogram = LengthHistogram::default();
+    histogram.record(HISTOGRAM_MAX_LEN);
+    histogram.record(HISTOGRAM_MAX_LEN + 20);
+
+    assert_eq!(histogram.counts()[HISTOGRAM_MAX_LEN - 1], 2);
+}
+
+#[test]
+fn typed_words_fill_the_length_histogram() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.record_typed("hello");
+    stats.record_escaped("world");
+
+    assert_eq!(stats.typed_lengths.counts()[4], 1);
+    assert_eq!(stats.typed_lengths.max_count(), 1);
+}
+
+#[test]
+fn streak_pitch_starts_at_the_normal_pitch() {
+    assert_eq!(streak_pitch(0), 1.0);
+    assert_eq!(streak_pitch(1), 1.0);
+}
+
+#[test]
+fn streak_pitch_rises_with_the_streak() {
+    assert!(streak_pitch(5) > streak_pitch(4));
+    assert!((streak_pitch(6) - (1.0 + 5.0 * STREAK_PITCH_STEP)).abs() < f32::EPSILON);
+}
+
+#[test]
+fn streak_pitch_is_capped() {
+    assert_eq!(streak_pitch(1000), MAX_STREAK_PITCH);
+}
+
+#[test]
+fn escaped_labels_leave_out_the_other_outcomes() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.record_typed("typed");
+    stats.record_escaped("first");
+    stats.record_shielded("shielded");
+    stats.record_escaped("second");
+
+    assert_eq!(stats.escaped_labels().collect::<Vec<&str>>(), vec!["first", "second"]);
+}
+
+#[test]
+fn typed_log_keeps_the_newest_words_first() {
+    let mut log = VecDeque::new();
+    for word in ["one", "two", "three"] {
+        push_typed_log(&mut log, word, 2);
+    }
+
+    assert_eq!(log, VecDeque::from(vec![String::from("three"), String::from("two")]));
+}
+
+#[test]
+fn accuracy_buckets_by_word_length() {
+    assert_eq!(LengthAccuracy::bucket(1), 0);
+    assert_eq!(LengthAccuracy::bucket(4), 0);
+    assert_eq!(LengthAccuracy::bucket(5), 1);
+    assert_eq!(LengthAccuracy::bucket(10), 2);
+    assert_eq!(LengthAccuracy::bucket(11), 3);
+    assert_eq!(LengthAccuracy::bucket(40), 3);
+
+    let labels = (0 .. ACCURACY_BUCKETS).map(LengthAccuracy::bucket_label).collect::<Vec<String>>();
+    assert_eq!(labels, vec!["1-4", "5-7", "8-10", "11+"]);
+}
+
+#[test]
+fn run_stats_track_the_accuracy_by_length() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    for word in ["cat", "dog", "elephant", "rhinoceros"] {
+        stats.record_spawned(word);
+    }
+    stats.record_typed("cat");
+    stats.record_escaped("dog");
+    stats.record_typed("elephant");
+
+    let accuracy = &stats.length_accuracy;
+    assert_eq!((accuracy.spawned(0), accuracy.typed(0), accuracy.escaped(0)), (2, 1, 1));
+    assert_eq!(accuracy.accuracy(0), Some(0.5));
+    assert_eq!(accuracy.accuracy(2), Some(1.0));
+    // the word still on the board counts as spawned only
+    assert_eq!(accuracy.spawned(2), 2);
+    assert_eq!(accuracy.accuracy(3), None);
+}
+
+#[test]
+fn accuracy_summary_lists_every_bucket() {
+    let mut accuracy = LengthAccuracy::default();
+    accuracy.record_spawned(3);
+    accuracy.record_spawned(3);
+    accuracy.record_typed(3);
+    accuracy.record_escaped(3);
+
+    assert_eq!(accuracy.summary(), "1-4 1/2 (50%), 5-7 0/0 (-), 8-10 0/0 (-), 11+ 0/0 (-)");
+}
+
+#[test]
+fn speedrun_summary_has_one_stat_per_line() {
+    let mut stats = RunStats::new(Difficulty::Normal);
+    stats.advance(90.0);
+    for _ in 0 .. 3 {
+        stats.record_typed("hello");
+    }
+    stats.record_escaped("world");
+
+    assert_eq!(stats.speedrun_summary(4), "Time 1:30\nWPM 2.0\nAccuracy 75%\nWords 3\nLifes 4");
+}
\ No newline at end of file